@@ -1,72 +1,263 @@
 use colored::Colorize;
 
-pub fn throw_undefined_class_error<'a>(
-    identifier: &str,
-    span_str: &str,
-    position: usize,
-    line: usize,
-    suggestion: &str,
-) -> String {
-    let mut hightlight = String::new();
-    let at_line = format!("At: {} | ", line);
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
+pub enum RuntimeErrorKind {
+    ERROR,
+    #[default]
+    TYPEERROR,
+    INDEXERROR,
+    KEYERROR,
+    REFERENCEERROR,
+    ARITHMETICERROR,
+    IOERROR,
+    IMPORTERROR,
+    SYNTAXERROR,
+    VALUEERROR,
+    PERMISSIONERROR,
+}
 
-    while hightlight.len() < position {
-        hightlight += " ";
+impl RuntimeErrorKind {
+    /// The built-in error classes: `Error` and its direct subclasses.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Error" => Some(RuntimeErrorKind::ERROR),
+            "TypeError" => Some(RuntimeErrorKind::TYPEERROR),
+            "IndexError" => Some(RuntimeErrorKind::INDEXERROR),
+            "KeyError" => Some(RuntimeErrorKind::KEYERROR),
+            "ReferenceError" => Some(RuntimeErrorKind::REFERENCEERROR),
+            "ArithmeticError" => Some(RuntimeErrorKind::ARITHMETICERROR),
+            "IOError" => Some(RuntimeErrorKind::IOERROR),
+            "ImportError" => Some(RuntimeErrorKind::IMPORTERROR),
+            "SyntaxError" => Some(RuntimeErrorKind::SYNTAXERROR),
+            "ValueError" => Some(RuntimeErrorKind::VALUEERROR),
+            "PermissionError" => Some(RuntimeErrorKind::PERMISSIONERROR),
+            _ => None,
+        }
     }
+}
 
-    at_line.chars().for_each(|c| {
-        hightlight += " ";
-    });
+#[derive(Debug, Default, Clone)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    pub message: String,
+    pub identifier: Option<String>,
+    pub suggestion: Option<String>,
+    /// Innermost call first, filled in while the error unwinds through function calls.
+    pub stack: Vec<String>,
+}
 
-    hightlight += " ";
+impl RuntimeError {
+    pub fn new(kind: RuntimeErrorKind, message: &str) -> Self {
+        RuntimeError {
+            kind,
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
 
-    identifier.chars().for_each(|c| {
-        hightlight += "^";
-    });
+    pub fn type_error(message: &str) -> Self {
+        RuntimeError::new(RuntimeErrorKind::TYPEERROR, message)
+    }
+
+    pub fn index_error(message: &str) -> Self {
+        RuntimeError::new(RuntimeErrorKind::INDEXERROR, message)
+    }
+
+    pub fn key_error(message: &str) -> Self {
+        RuntimeError::new(RuntimeErrorKind::KEYERROR, message)
+    }
+
+    pub fn arithmetic_error(message: &str) -> Self {
+        RuntimeError::new(RuntimeErrorKind::ARITHMETICERROR, message)
+    }
+
+    pub fn io_error(message: &str) -> Self {
+        RuntimeError::new(RuntimeErrorKind::IOERROR, message)
+    }
+
+    pub fn import_error(message: &str) -> Self {
+        RuntimeError::new(RuntimeErrorKind::IMPORTERROR, message)
+    }
+
+    pub fn syntax_error(message: &str) -> Self {
+        RuntimeError::new(RuntimeErrorKind::SYNTAXERROR, message)
+    }
+
+    /// A value of the right type that cannot be used, such as text that is not a number
+    pub fn value_error(message: &str) -> Self {
+        RuntimeError::new(RuntimeErrorKind::VALUEERROR, message)
+    }
+
+    /// A native function that reaches outside the interpreter without `--allow-process`
+    pub fn permission_denied(function: &str) -> Self {
+        RuntimeError {
+            identifier: Some(function.to_string()),
+            ..RuntimeError::new(
+                RuntimeErrorKind::PERMISSIONERROR,
+                &format!("`{function}` needs the process permission"),
+            )
+        }
+        .with_suggestion("Run the interpreter with `--allow-process` to grant it")
+    }
+
+    /// Every task left waits for a future that only another waiting task could settle
+    pub fn stalled_tasks() -> Self {
+        RuntimeError::new(
+            RuntimeErrorKind::ERROR,
+            "The awaited future can never settle, its tasks wait for each other",
+        )
+        .with_suggestion("Check for async functions that await each other's futures")
+    }
 
-    format!(
-        "{}: Cannot access member of undefined: {}
-        {at_line}{}
-        {}
-        {}",
-        "Error".red().bold(),
-        format!("`{identifier}`").bold(),
-        format!("`{span_str}`").yellow().bold(),
-        hightlight.red(),
-        suggestion.cyan().bold()
-    )
+    pub fn undefined(identifier: &str) -> Self {
+        RuntimeError {
+            kind: RuntimeErrorKind::REFERENCEERROR,
+            message: format!("`{identifier}` is undefined"),
+            identifier: Some(identifier.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// `.` used on an enum or a module, which only have static members
+    pub fn static_access(parent: &str, member: &str) -> Self {
+        RuntimeError {
+            identifier: Some(parent.to_string()),
+            ..RuntimeError::type_error(&format!(
+                "`{parent}` is not a value, its members are accessed with `::`"
+            ))
+        }
+        .with_suggestion(&format!("Use `{parent}::{member}` instead"))
+    }
+
+    /// `::` used on a value, whose members are accessed with `.`
+    pub fn instance_access(type_name: &str, member: &str) -> Self {
+        RuntimeError {
+            identifier: Some(member.to_string()),
+            ..RuntimeError::type_error(&format!(
+                "`::` accesses enums and modules, found a value of type `{type_name}`"
+            ))
+        }
+        .with_suggestion(&format!("Use `.{member}` to access a member of a value"))
+    }
+
+    pub fn with_suggestion(mut self, suggestion: &str) -> Self {
+        self.suggestion = Some(suggestion.to_string());
+        self
+    }
+
+    /// Every error is an `Error`, and an instance of the class named after its kind.
+    pub fn is_instance_of(&self, class_name: &str) -> bool {
+        class_name == "Error" || class_name == self.kind_name()
+    }
+
+    pub fn kind_name(&self) -> &'static str {
+        match self.kind {
+            RuntimeErrorKind::ERROR => "Error",
+            RuntimeErrorKind::TYPEERROR => "TypeError",
+            RuntimeErrorKind::INDEXERROR => "IndexError",
+            RuntimeErrorKind::KEYERROR => "KeyError",
+            RuntimeErrorKind::REFERENCEERROR => "ReferenceError",
+            RuntimeErrorKind::ARITHMETICERROR => "ArithmeticError",
+            RuntimeErrorKind::IOERROR => "IOError",
+            RuntimeErrorKind::IMPORTERROR => "ImportError",
+            RuntimeErrorKind::SYNTAXERROR => "SyntaxError",
+            RuntimeErrorKind::VALUEERROR => "ValueError",
+            RuntimeErrorKind::PERMISSIONERROR => "PermissionError",
+        }
+    }
 }
 
-pub fn throw_undefined(
-    identifier: &str,
-    span_str: &str,
-    position: usize,
-    line: usize,
-) -> String {
+fn highlight(identifier: &str, position: usize, at_line: &str) -> String {
     let mut hightlight = String::new();
-    let at_line = format!("At: {} | ", line);
 
     while hightlight.len() < position {
         hightlight += " ";
     }
 
-    at_line.chars().for_each(|c| {
+    at_line.chars().for_each(|_| {
         hightlight += " ";
     });
 
     hightlight += " ";
 
-    identifier.chars().for_each(|c| {
+    identifier.chars().for_each(|_| {
         hightlight += "^";
     });
 
-    format!(
-        "{}: {} is undefined
-        {at_line}{}
-        {}",
-        "Error".red().bold(),
-        format!("`{identifier}`").bold(),
-        format!("`{span_str}`").yellow().bold(),
-        hightlight.red(),
-    )
-}
\ No newline at end of file
+    hightlight
+}
+
+/// Levenshtein distance, used to suggest the name that was probably meant.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// An error that escaped a file, with the top level item that raised it.
+#[derive(Debug)]
+pub struct ProgramError {
+    pub error: RuntimeError,
+    pub span_str: String,
+    pub line: usize,
+}
+
+pub fn throw_runtime_error(error: &RuntimeError, span_str: &str, line: usize) -> String {
+    // A span can cover several lines, only the one mentioning the identifier is shown
+    let line_offset = match &error.identifier {
+        Some(identifier) => span_str
+            .find(identifier.as_str())
+            .map_or(0, |position| span_str[..position].matches('\n').count()),
+        None => 0,
+    };
+    let span_str = span_str.lines().nth(line_offset).unwrap_or(span_str).trim();
+
+    let at_line = format!("At: {} | ", line + line_offset);
+    let hightlight = match &error.identifier {
+        Some(identifier) => match span_str.find(identifier.as_str()) {
+            Some(position) => highlight(identifier, position, &at_line),
+            None => String::new(),
+        },
+        None => String::new(),
+    };
+
+    let mut message = format!("{}: {}", error.kind_name().red().bold(), error.message.bold());
+
+    if !span_str.is_empty() {
+        message += &format!("\n        {at_line}{}", format!("`{span_str}`").yellow().bold());
+    }
+
+    if !hightlight.is_empty() {
+        message += &format!("\n        {}", hightlight.red());
+    }
+
+    if let Some(suggestion) = &error.suggestion {
+        message += &format!("\n        {}", suggestion.cyan().bold());
+    }
+
+    // A deep recursion repeats the same frame, it is shown once with a count
+    let mut frames = error.stack.iter().peekable();
+    while let Some(frame) = frames.next() {
+        message += &format!("\n            {}", frame.dimmed());
+        let mut repeated = 0;
+        while frames.next_if_eq(&frame).is_some() {
+            repeated += 1;
+        }
+        if repeated > 0 {
+            message += &format!("\n            {}", format!("... repeated {repeated} more time(s)").dimmed());
+        }
+    }
+
+    message
+}
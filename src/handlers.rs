@@ -1,10 +1,34 @@
 use pest::iterators::Pair;
 
-use crate::{structure::{ImportedModule, Argument, ArgumentKind, Expression, UnaryExpression, UnaryExpressionKind, BinaryExpression, AffixExpression, FunctionCall, MemberAccess, MemberAccessKind, TypeKind, VarDeclaration, Declaration, Type, DeclarationKind}, Rule};
+use crate::{
+    numeric::{parse_float_literal, parse_integer_literal},
+    runtime::Value,
+    structure::{
+        AccessModifier, AffixExpression, Argument, Assignment, BinaryExpression,
+        Block, CatchClause, ClassDeclaration, ConstDeclaration, Declaration, DeclarationKind, EnumDeclaration,
+        EnumVariant, Expression, ExpressionKind, ForStatement, ForStatementKind, FunctionCall,
+        FunctionDeclaration, IfStatement, IfStatementKind, ImportedItem, ImportedModule, IndexExpression,
+        CallExpression,
+        InterfaceDeclaration, Literal, LiteralKind, MapEntry, MatchArm, MatchExpression,
+        MemberAccess, MemberAccessKind, Parameter, Pattern, PatternKind, ReturnStatement,
+        Statement, StatementKind, StringSegment, StringSegmentKind, ThrowStatement, TryStatement,
+        Type, TypeKind, UnaryExpression, UnaryExpressionKind, VarDeclaration, WhileStatement,
+    },
+    Rule,
+};
 
 pub fn handle_import_stmt(pair: Pair<'_, Rule>) -> ImportedModule {
     let mut module = ImportedModule::default();
-    let module_name = pair.into_inner().next().unwrap();
+    let mut inner_pairs = pair.into_inner();
+    let module_name = inner_pairs.next().unwrap();
+
+    for inner_pair in inner_pairs {
+        match inner_pair.as_rule() {
+            Rule::import_alias => module.alias = Some(handle_import_alias(inner_pair)),
+            Rule::import_list => module.items = inner_pair.into_inner().map(handle_import_item).collect(),
+            _ => {}
+        }
+    }
 
     module.full_path = module_name.as_str().to_string();
     module.parent = module_name
@@ -24,56 +48,166 @@ pub fn handle_import_stmt(pair: Pair<'_, Rule>) -> ImportedModule {
     module
 }
 
+pub fn handle_import_item(pair: Pair<'_, Rule>) -> ImportedItem {
+    let mut item = ImportedItem::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::identifier => item.identifier = handle_identifier(inner_pair),
+            Rule::import_alias => item.alias = Some(handle_import_alias(inner_pair)),
+            _ => {}
+        }
+    }
+
+    item
+}
+
+pub fn handle_import_alias(pair: Pair<'_, Rule>) -> String {
+    handle_identifier(pair.into_inner().next().unwrap())
+}
+
 pub fn handle_args_list(pair: Pair<'_, Rule>) -> Vec<Argument> {
-    let mut args_list = Vec::new();
+    pair.into_inner().map(handle_argument).collect()
+}
+
+pub fn handle_argument(pair: Pair<'_, Rule>) -> Argument {
+    let mut argument = Argument::default();
+
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
-            Rule::expression => {
-                let mut argument = Argument::default();
-                argument.argument_kind = ArgumentKind::EXPRESSION;
-                argument.expression = Some(handle_expression(inner_pair));
-                args_list.push(argument);
-            }
-            Rule::identifier => {
-                let mut argument = Argument::default();
-                argument.argument_kind = ArgumentKind::IDENTIFIER;
-                argument.identifier = Some(inner_pair.as_str().to_string());
-                args_list.push(argument);
-            }
+            Rule::identifier => argument.identifier = Some(handle_identifier(inner_pair)),
+            Rule::expression => argument.expression = handle_expression(inner_pair),
             _ => {}
         }
     }
 
-    args_list
+    argument
 }
 
 pub fn handle_expression(pair: Pair<'_, Rule>) -> Expression {
-    let mut expression = Expression::default();
+    let mut terms = Vec::new();
+    let mut operators = Vec::new();
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
-            Rule::binary_expression => {
-                expression.binary_expression = Some(handle_binary_expression(inner_pair))
-            }
-            Rule::unary_expression => {
-                expression.unary_expression = Some(handle_unary_expression(inner_pair))
-            }
-            Rule::prefix_expression => {
-                expression.prefix_expression = Some(handle_prefix_expression(inner_pair))
-            }
-            Rule::postfix_expression => {
-                expression.postfix_expression = Some(handle_prefix_expression(inner_pair))
-            }
-            Rule::member_access => {
-                expression.member_access = Some(handle_member_access(inner_pair))
-            }
-            _ => {}
+            Rule::binary_operator => operators.push(handle_binary_operator(inner_pair)),
+            _ => terms.push(handle_operand(inner_pair)),
         }
     }
 
+    fold_binary_expression(terms, operators)
+}
+
+pub fn handle_operand(pair: Pair<'_, Rule>) -> Expression {
+    let mut expression = Expression::default();
+
+    match pair.as_rule() {
+        Rule::unary_expression => {
+            expression.expression_kind = ExpressionKind::UNARY;
+            expression.unary_expression = Some(handle_unary_expression(pair));
+        }
+        Rule::identifier => {
+            let unary_expression = UnaryExpression {
+                kind: UnaryExpressionKind::IDENTIFIER,
+                string_value: Some(handle_identifier(pair)),
+                ..Default::default()
+            };
+            expression.expression_kind = ExpressionKind::UNARY;
+            expression.unary_expression = Some(unary_expression);
+        }
+        Rule::function_call => {
+            let unary_expression = UnaryExpression {
+                kind: UnaryExpressionKind::FUNCTIONCALL,
+                function_call: Some(handle_function_call(pair)),
+                ..Default::default()
+            };
+            expression.expression_kind = ExpressionKind::UNARY;
+            expression.unary_expression = Some(unary_expression);
+        }
+        Rule::match_expression => {
+            let unary_expression = UnaryExpression {
+                kind: UnaryExpressionKind::MATCH,
+                match_expression: Some(handle_match_expression(pair)),
+                ..Default::default()
+            };
+            expression.expression_kind = ExpressionKind::UNARY;
+            expression.unary_expression = Some(unary_expression);
+        }
+        Rule::prefix_expression => {
+            expression.expression_kind = ExpressionKind::PREFIX;
+            expression.prefix_expression = Some(handle_prefix_expression(pair));
+        }
+        Rule::postfix_expression if pair.clone().into_inner().len() == 1 => {
+            return handle_operand(pair.into_inner().next().unwrap());
+        }
+        Rule::postfix_expression => {
+            expression.expression_kind = ExpressionKind::POSTFIX;
+            expression.postfix_expression = Some(handle_postfix_expression(pair));
+        }
+        Rule::member_access => return handle_member_access(pair),
+        Rule::index_expression => {
+            expression.expression_kind = ExpressionKind::INDEX;
+            expression.index_expression = Some(handle_index_expression(pair));
+        }
+        _ => {}
+    }
+
     expression
 }
 
+fn binary_operator_precedence(operator: &str) -> u8 {
+    match operator {
+        "||" => 1,
+        "&&" => 2,
+        "==" | "!=" => 3,
+        "<" | ">" | "<=" | ">=" => 4,
+        "+" | "-" => 5,
+        "*" | "/" | "%" => 6,
+        _ => 0,
+    }
+}
+
+fn reduce_binary_expression(output: &mut Vec<Expression>, operator: String) {
+    let second_term = output.pop().unwrap();
+    let first_term = output.pop().unwrap();
+
+    let expression = Expression {
+        expression_kind: ExpressionKind::BINARY,
+        binary_expression: Some(BinaryExpression {
+            first_term: Box::new(first_term),
+            operator,
+            second_term: Box::new(second_term),
+        }),
+        ..Default::default()
+    };
+    output.push(expression);
+}
+
+/// Folds `a op b op c ...` into a left-associative tree, honouring operator precedence.
+fn fold_binary_expression(terms: Vec<Expression>, operators: Vec<String>) -> Expression {
+    let mut terms = terms.into_iter();
+    let mut output = vec![terms.next().unwrap_or_default()];
+    let mut pending: Vec<String> = Vec::new();
+
+    for (operator, term) in operators.into_iter().zip(terms) {
+        while let Some(top) = pending.last() {
+            if binary_operator_precedence(top) < binary_operator_precedence(&operator) {
+                break;
+            }
+            let top = pending.pop().unwrap();
+            reduce_binary_expression(&mut output, top);
+        }
+        pending.push(operator);
+        output.push(term);
+    }
+
+    while let Some(operator) = pending.pop() {
+        reduce_binary_expression(&mut output, operator);
+    }
+
+    output.pop().unwrap()
+}
+
 pub fn handle_unary_expression(pair: Pair<'_, Rule>) -> UnaryExpression {
     let mut unary_expression = UnaryExpression::default();
     for inner_pair in pair.into_inner() {
@@ -83,13 +217,26 @@ pub fn handle_unary_expression(pair: Pair<'_, Rule>) -> UnaryExpression {
                 unary_expression.kind = UnaryExpressionKind::IDENTIFIER;
             }
             Rule::literal => {
-                unary_expression.string_value = Some(handle_literal(inner_pair));
+                unary_expression.literal = Some(handle_literal(inner_pair));
                 unary_expression.kind = UnaryExpressionKind::LITERAL;
             }
             Rule::function_call => {
                 unary_expression.function_call = Some(handle_function_call(inner_pair));
                 unary_expression.kind = UnaryExpressionKind::FUNCTIONCALL;
             }
+            Rule::match_expression => {
+                unary_expression.match_expression = Some(handle_match_expression(inner_pair));
+                unary_expression.kind = UnaryExpressionKind::MATCH;
+            }
+            Rule::await_expression => {
+                let operand = inner_pair.into_inner().nth(1).unwrap();
+                unary_expression.expression = Some(Box::new(handle_operand(operand)));
+                unary_expression.kind = UnaryExpressionKind::AWAIT;
+            }
+            Rule::lambda_expression => {
+                unary_expression.lambda = Some(handle_lambda_expression(inner_pair));
+                unary_expression.kind = UnaryExpressionKind::LAMBDA;
+            }
             Rule::expression => {
                 unary_expression.expression = Some(Box::new(handle_expression(inner_pair)));
                 unary_expression.kind = UnaryExpressionKind::EXPRESSION;
@@ -101,53 +248,176 @@ pub fn handle_unary_expression(pair: Pair<'_, Rule>) -> UnaryExpression {
     unary_expression
 }
 
-pub fn handle_binary_expression(pair: Pair<'_, Rule>) -> BinaryExpression {
-    let mut binary_expression = BinaryExpression::default();
-    let mut idx = 0;
+pub fn handle_match_expression(pair: Pair<'_, Rule>) -> MatchExpression {
+    let mut match_expression = MatchExpression::default();
+
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
-            Rule::unary_expression => {
-                idx += 1;
+            Rule::expression => {
+                match_expression.expression = Box::new(handle_expression(inner_pair));
+            }
+            Rule::match_arm => match_expression.arms.push(handle_match_arm(inner_pair)),
+            _ => {}
+        }
+    }
 
-                if idx < 1 {
-                    binary_expression.first_term = handle_unary_expression(inner_pair);
-                } else {
-                    binary_expression.second_term = handle_unary_expression(inner_pair);
-                }
+    match_expression
+}
+
+pub fn handle_match_arm(pair: Pair<'_, Rule>) -> MatchArm {
+    let mut match_arm = MatchArm::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::pattern => match_arm.pattern = handle_pattern(inner_pair),
+            // The guard comes before the body, so the first expression is the guard when
+            // the arm has both
+            Rule::expression if match_arm.guard.is_none() && match_arm.expression.is_none() => {
+                match_arm.expression = Some(handle_expression(inner_pair));
+            }
+            Rule::expression => {
+                match_arm.guard = match_arm.expression.take();
+                match_arm.expression = Some(handle_expression(inner_pair));
             }
-            Rule::binary_operator => {
-                binary_expression.operator = handle_binary_operator(inner_pair);
+            Rule::block => {
+                match_arm.guard = match_arm.expression.take();
+                match_arm.block = Some(handle_block(inner_pair));
             }
             _ => {}
         }
     }
 
-    binary_expression
+    match_arm
+}
+
+pub fn handle_pattern(pair: Pair<'_, Rule>) -> Pattern {
+    let mut pattern = Pattern::default();
+    let inner_pair = pair.into_inner().next().unwrap();
+
+    match inner_pair.as_rule() {
+        Rule::wildcard_pattern => pattern.pattern_kind = PatternKind::WILDCARD,
+        Rule::identifier => {
+            pattern.pattern_kind = PatternKind::BINDING;
+            pattern.identifier = handle_identifier(inner_pair);
+        }
+        Rule::literal => {
+            pattern.pattern_kind = PatternKind::LITERAL;
+            pattern.literal = Some(handle_literal(inner_pair));
+        }
+        Rule::variant_pattern => {
+            let mut inner_pairs = inner_pair.into_inner();
+            pattern.pattern_kind = PatternKind::VARIANT;
+            pattern.enum_identifier = handle_identifier(inner_pairs.next().unwrap());
+            pattern.identifier = handle_identifier(inner_pairs.next().unwrap());
+            pattern.payload = inner_pairs.map(handle_pattern).collect();
+        }
+        _ => {}
+    }
+
+    pattern
+}
+
+/// Lambdas are lowered to anonymous function declarations, an expression body
+/// becomes a block returning that expression.
+pub fn handle_lambda_expression(pair: Pair<'_, Rule>) -> FunctionDeclaration {
+    let mut function_declaration = FunctionDeclaration {
+        identifier: String::from("lambda"),
+        ..Default::default()
+    };
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::parameter_list => {
+                function_declaration.parameter_list = handle_parameter_list(inner_pair);
+            }
+            Rule::r#type => function_declaration.return_type = handle_type(inner_pair),
+            Rule::block => function_declaration.block = handle_block(inner_pair),
+            Rule::expression => {
+                let statement = Statement {
+                    statement_kind: StatementKind::RETURNSTMT,
+                    return_statement: Some(ReturnStatement {
+                        expression: Some(handle_expression(inner_pair)),
+                    }),
+                    ..Default::default()
+                };
+                function_declaration.block.statements.push(statement);
+            }
+            _ => {}
+        }
+    }
+
+    function_declaration
 }
 
 pub fn handle_prefix_expression(pair: Pair<'_, Rule>) -> AffixExpression {
-    let mut affix_expression = AffixExpression::default();
+    let mut inner_pairs = pair.into_inner();
+    let operator = inner_pairs.next().unwrap().as_str().to_string();
 
-    affix_expression
+    AffixExpression {
+        operator,
+        operand: Box::new(handle_operand(inner_pairs.next().unwrap())),
+    }
 }
 
 pub fn handle_postfix_expression(pair: Pair<'_, Rule>) -> AffixExpression {
-    let mut affix_expression = AffixExpression::default();
+    let mut inner_pairs = pair.into_inner();
+    let operand = handle_operand(inner_pairs.next().unwrap());
 
-    affix_expression
+    AffixExpression {
+        operator: inner_pairs.next().unwrap().as_str().to_string(),
+        operand: Box::new(operand),
+    }
+}
+
+pub fn handle_index_expression(pair: Pair<'_, Rule>) -> IndexExpression {
+    let mut index_expression = IndexExpression::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::identifier => index_expression.identifier = handle_identifier(inner_pair),
+            Rule::expression => index_expression
+                .indices
+                .push(handle_expression(inner_pair)),
+            _ => {}
+        }
+    }
+
+    index_expression
 }
 
 pub fn handle_identifier(pair: Pair<'_, Rule>) -> String {
     pair.as_str().to_string()
 }
 
-pub fn handle_literal(pair: Pair<'_, Rule>) -> String {
-    let mut literal = String::new();
+pub fn handle_literal(pair: Pair<'_, Rule>) -> Literal {
+    let mut literal = Literal::default();
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
-            Rule::number_literal => literal = handle_number_literal(inner_pair),
-            Rule::string_literal => literal = handle_string_literal(inner_pair),
-            Rule::boolean_literal => literal = handle_boolean_literal(inner_pair),
+            Rule::number_literal => {
+                literal.literal_kind = match inner_pair.clone().into_inner().next().unwrap().as_rule() {
+                    Rule::float_literal => LiteralKind::FLOAT,
+                    _ => LiteralKind::NUMBER,
+                };
+                literal.value = inner_pair.as_str().to_string();
+                literal.number = Some(handle_number_literal(inner_pair));
+            }
+            Rule::string_literal => {
+                literal.literal_kind = LiteralKind::STRING;
+                literal.segments = handle_string_literal(inner_pair);
+            }
+            Rule::boolean_literal => {
+                literal.literal_kind = LiteralKind::BOOLEAN;
+                literal.value = handle_boolean_literal(inner_pair);
+            }
+            Rule::null_literal => literal.literal_kind = LiteralKind::NULL,
+            Rule::array_literal => {
+                literal.literal_kind = LiteralKind::ARRAY;
+                literal.elements = handle_array_literal(inner_pair);
+            }
+            Rule::map_literal => {
+                literal.literal_kind = LiteralKind::MAP;
+                literal.entries = handle_map_literal(inner_pair);
+            }
             _ => {}
         }
     }
@@ -155,28 +425,126 @@ pub fn handle_literal(pair: Pair<'_, Rule>) -> String {
     literal
 }
 
-pub fn handle_number_literal(pair: Pair<'_, Rule>) -> String {
-    pair.as_str().to_string()
+/// Literals that do not fit were reported by `validate_program` before the program ran
+pub fn handle_number_literal(pair: Pair<'_, Rule>) -> Value {
+    let literal = pair.as_str();
+    match pair.into_inner().next().unwrap().as_rule() {
+        Rule::float_literal => Value::FLOAT(parse_float_literal(literal).unwrap()),
+        _ => Value::INT(parse_integer_literal(literal).unwrap()),
+    }
 }
 
-pub fn handle_string_literal(pair: Pair<'_, Rule>) -> String {
-    pair.as_str().to_string()
+pub fn handle_string_literal(pair: Pair<'_, Rule>) -> Vec<StringSegment> {
+    let mut segments: Vec<StringSegment> = Vec::new();
+    let mut inner_pairs: Vec<Pair<'_, Rule>> = pair.into_inner().collect();
+
+    if let Some(first) = inner_pairs.first() {
+        if first.as_rule() == Rule::triple_quoted {
+            inner_pairs = inner_pairs.remove(0).into_inner().collect();
+
+            // A line break right after the opening quotes is not part of the string
+            if let Some(first) = inner_pairs.first() {
+                if first.as_rule() == Rule::triple_text && first.as_str().starts_with(['\n', '\r']) {
+                    let text = first.as_str().trim_start_matches('\r');
+                    let text = text.strip_prefix('\n').unwrap_or(text).to_string();
+                    inner_pairs.remove(0);
+                    push_text(&mut segments, &text);
+                }
+            }
+        }
+    }
+
+    for inner_pair in inner_pairs {
+        match inner_pair.as_rule() {
+            Rule::string_text | Rule::triple_text => push_text(&mut segments, inner_pair.as_str()),
+            // Escapes that name no character were reported by `validate_program`
+            Rule::escape_sequence => {
+                let character = handle_escape_sequence(inner_pair).unwrap();
+                push_text(&mut segments, &character.to_string());
+            }
+            Rule::interpolation => {
+                segments.push(StringSegment {
+                    segment_kind: StringSegmentKind::INTERPOLATION,
+                    expression: Some(handle_expression(inner_pair.into_inner().next().unwrap())),
+                    ..Default::default()
+                });
+            }
+            _ => {}
+        }
+    }
+
+    segments
+}
+
+fn push_text(segments: &mut Vec<StringSegment>, text: &str) {
+    if let Some(StringSegment {
+        segment_kind: StringSegmentKind::TEXT,
+        text: previous,
+        ..
+    }) = segments.last_mut()
+    {
+        previous.push_str(text);
+        return;
+    }
+
+    segments.push(StringSegment {
+        text: text.to_string(),
+        ..Default::default()
+    });
+}
+
+/// `None` for a `\u{...}` escape that is not a Unicode scalar value, such as a surrogate
+pub fn handle_escape_sequence(pair: Pair<'_, Rule>) -> Option<char> {
+    let escape = &pair.as_str()[1..];
+    match escape {
+        "n" => Some('\n'),
+        "t" => Some('\t'),
+        "r" => Some('\r'),
+        "0" => Some('\0'),
+        _ if escape.starts_with("u{") => {
+            let code_point = u32::from_str_radix(&escape[2..escape.len() - 1], 16).unwrap();
+            char::from_u32(code_point)
+        }
+        _ => escape.chars().next(),
+    }
 }
 
 pub fn handle_boolean_literal(pair: Pair<'_, Rule>) -> String {
     pair.as_str().to_string()
 }
 
+pub fn handle_array_literal(pair: Pair<'_, Rule>) -> Vec<Expression> {
+    pair.into_inner().map(handle_expression).collect()
+}
+
+pub fn handle_map_literal(pair: Pair<'_, Rule>) -> Vec<MapEntry> {
+    let mut entries = Vec::new();
+
+    for map_entry in pair.into_inner() {
+        let mut inner_pairs = map_entry.into_inner();
+        entries.push(MapEntry {
+            key: handle_expression(inner_pairs.next().unwrap()),
+            value: handle_expression(inner_pairs.next().unwrap()),
+        });
+    }
+
+    entries
+}
+
 pub fn handle_binary_operator(pair: Pair<'_, Rule>) -> String {
     pair.as_str().to_string()
 }
 
 pub fn handle_function_call(pair: Pair<'_, Rule>) -> FunctionCall {
-    let mut function_call = FunctionCall::default();
+    let mut function_call = FunctionCall {
+        line: pair.as_span().start_pos().line_col().0,
+        ..Default::default()
+    };
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
             Rule::identifier => function_call.identifier = handle_identifier(inner_pair),
+            Rule::type_argument => function_call.type_arguments = handle_type_argument(inner_pair),
             Rule::arg_list => function_call.args_list = handle_args_list(inner_pair),
             _ => {}
         }
@@ -185,26 +553,68 @@ pub fn handle_function_call(pair: Pair<'_, Rule>) -> FunctionCall {
     function_call
 }
 
-pub fn handle_method_call(pair: Pair<'_, Rule>) -> FunctionCall {
-    handle_function_call(pair.into_inner().next().unwrap())
+/// Folds a chain of member, call and index links onto the expression before them, so
+/// `a.b(1)[0]` becomes an index into the result of calling `b` on `a`.
+pub fn handle_member_access(pair: Pair<'_, Rule>) -> Expression {
+    let mut inner_pairs = pair.into_inner();
+    let mut object = handle_operand(inner_pairs.next().unwrap());
+
+    for link in inner_pairs {
+        let mut expression = Expression::default();
+        let line = link.as_span().start_pos().line_col().0;
+
+        match link.as_rule() {
+            Rule::member_link => {
+                expression.expression_kind = ExpressionKind::MEMBERACCESS;
+                let mut member_access = handle_member_link(link, object);
+                member_access.line = line;
+                expression.member_access = Some(member_access);
+            }
+            Rule::call_link => {
+                let args_list = match link.into_inner().next() {
+                    Some(arg_list) => handle_args_list(arg_list),
+                    None => Vec::new(),
+                };
+                expression.expression_kind = ExpressionKind::CALL;
+                expression.call_expression = Some(CallExpression {
+                    callee: Box::new(object),
+                    args_list,
+                    line,
+                });
+            }
+            Rule::index_link => {
+                let index = handle_expression(link.into_inner().next().unwrap());
+                expression.expression_kind = ExpressionKind::INDEX;
+                expression.index_expression = Some(push_index(object, index));
+            }
+            _ => unreachable!(),
+        }
+
+        object = expression;
+    }
+
+    object
 }
 
-pub fn handle_member_access(pair: Pair<'_, Rule>) -> MemberAccess {
-    let mut member_access = MemberAccess::default();
+fn handle_member_link(pair: Pair<'_, Rule>, object: Expression) -> MemberAccess {
+    let mut member_access = MemberAccess {
+        object: Box::new(object),
+        ..Default::default()
+    };
+
+    // The parentheses themselves are silent, so an empty call is only visible in the text
+    if pair.as_str().trim_end().ends_with(')') {
+        member_access.kind = MemberAccessKind::METHOD;
+    }
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
-            Rule::unary_expression => {
-                member_access.parent = handle_unary_expression(inner_pair).string_value.unwrap()
-            }
+            Rule::access_operator => member_access.is_static = inner_pair.as_str() == "::",
             Rule::identifier => member_access.identifier = handle_identifier(inner_pair),
-            Rule::method_call => {
-                // get the identifier and args_list
-                let mut function_call = handle_method_call(inner_pair);
-                member_access.identifier = function_call.identifier;
-                member_access.args_list = function_call.args_list;
-                member_access.kind = MemberAccessKind::METHOD
+            Rule::type_argument => {
+                member_access.type_arguments = Some(handle_type_argument(inner_pair));
             }
+            Rule::arg_list => member_access.args_list = handle_args_list(inner_pair),
             _ => {}
         }
     }
@@ -212,76 +622,123 @@ pub fn handle_member_access(pair: Pair<'_, Rule>) -> MemberAccess {
     member_access
 }
 
+/// Consecutive indices share one `IndexExpression`, and a variable keeps being indexed by
+/// name so that `xs[0][1]` stays assignable.
+fn push_index(object: Expression, index: Expression) -> IndexExpression {
+    if let ExpressionKind::INDEX = object.expression_kind {
+        let mut index_expression = object.index_expression.unwrap();
+        index_expression.indices.push(index);
+        return index_expression;
+    }
+
+    let mut index_expression = IndexExpression::default();
+    match object.as_identifier() {
+        Some(identifier) => index_expression.identifier = identifier.to_string(),
+        None => index_expression.object = Some(Box::new(object)),
+    }
+    index_expression.indices.push(index);
+    index_expression
+}
+
 pub fn handle_type(pair: Pair<'_, Rule>) -> Type {
+    handle_type_pair(pair.into_inner().next().unwrap())
+}
+
+fn handle_type_pair(type_pair: Pair<'_, Rule>) -> Type {
     let mut type_def = Type::default();
-    let type_pair = pair.into_inner().next().unwrap();
 
     match type_pair.as_rule() {
         Rule::simple_type => {
             type_def.type_identifier = type_pair.as_str().to_string();
-            type_def.type_kind = TypeKind::SIMPLE
+            type_def.type_kind = match type_pair.into_inner().next().unwrap().as_rule() {
+                Rule::identifier => TypeKind::SIMPLE,
+                _ => TypeKind::BUILTIN,
+            };
         }
         Rule::generic_type => {
             type_def.type_kind = TypeKind::GENERIC;
+            // the built-in names are string literals in the grammar, so they have no pair
             type_def.type_identifier = type_pair
-                .clone()
-                .into_inner()
-                .nth(0)
-                .unwrap()
                 .as_str()
+                .split('<')
+                .next()
+                .unwrap()
+                .trim()
                 .to_string();
-            for type_parameter_identifier in type_pair.into_inner().nth(1).unwrap().into_inner() {
-                type_def
-                    .type_parameters
-                    .push(type_parameter_identifier.as_str().to_string());
+            for inner_pair in type_pair.into_inner() {
+                if let Rule::type_parameter = inner_pair.as_rule() {
+                    type_def.type_parameters = inner_pair.into_inner().map(handle_type).collect();
+                }
             }
         }
-        Rule::type_array => {
-            type_def.type_kind = TypeKind::TYPEARRAY;
-            type_def.is_type_array = true;
+        Rule::function_type => {
+            let mut types: Vec<Type> = type_pair.into_inner().map(handle_type).collect();
 
-            type_def.type_identifier = type_pair
-                .clone()
-                .into_inner()
-                .nth(0)
-                .unwrap()
-                .as_str()
-                .to_string();
+            type_def.type_kind = TypeKind::FUNCTION;
+            type_def.type_identifier = String::from("function");
+            type_def.return_type = types.pop().map(Box::new);
+            type_def.type_parameters = types;
+        }
+        // `int[][]` is an array of `int[]`, each suffix wraps the type once more
+        Rule::type_array => {
+            let mut inner_pairs = type_pair.into_inner();
+            type_def = handle_type_pair(inner_pairs.next().unwrap());
 
-            for sub_type_pair in type_pair.into_inner() {
-                match sub_type_pair.as_rule() {
-                    Rule::simple_type => {
-                        type_def.type_identifier = sub_type_pair.as_str().to_string().clone();
-                    }
-                    Rule::generic_type => {
-                        for type_parameter_identifier in
-                            sub_type_pair.into_inner().nth(1).unwrap().into_inner()
-                        {
-                            type_def
-                                .type_parameters
-                                .push(type_parameter_identifier.as_str().to_string());
-                        }
-                    }
-                    _ => {}
-                }
+            for _ in inner_pairs {
+                type_def = Type {
+                    type_kind: TypeKind::TYPEARRAY,
+                    is_type_array: true,
+                    type_identifier: type_def.type_identifier.clone(),
+                    element_type: Some(Box::new(type_def)),
+                    ..Default::default()
+                };
             }
         }
         _ => {
             type_def.type_identifier = type_pair.as_str().to_string();
-            type_def.type_kind = TypeKind::TYPEARRAY;
         }
     }
 
     type_def
 }
 
+pub fn handle_type_argument(pair: Pair<'_, Rule>) -> Vec<Type> {
+    pair.into_inner().map(handle_type).collect()
+}
+
+pub fn handle_access_modifier(pair: Pair<'_, Rule>) -> AccessModifier {
+    match pair.as_str() {
+        "public" => AccessModifier::PUBLIC,
+        "protected" => AccessModifier::PROTECTED,
+        _ => AccessModifier::PRIVATE,
+    }
+}
+
+pub fn push_doc_comment(doc_comment: &mut Option<String>, pair: Pair<'_, Rule>) {
+    let line = pair.as_str().trim_end_matches(['\r', '\n']);
+    let line = line.strip_prefix("///").unwrap();
+    let line = line.strip_prefix(' ').unwrap_or(line);
+
+    match doc_comment {
+        Some(doc_comment) => {
+            doc_comment.push('\n');
+            doc_comment.push_str(line);
+        }
+        None => *doc_comment = Some(line.to_string()),
+    }
+}
+
 pub fn handle_var_declaration(pair: Pair<'_, Rule>) -> VarDeclaration {
     let mut var_declaration = VarDeclaration::default();
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
+            Rule::doc_comment => push_doc_comment(&mut var_declaration.doc_comment, inner_pair),
+            Rule::access_modifier => {
+                var_declaration.access_modifier = handle_access_modifier(inner_pair);
+            }
             Rule::r#type => {
-                var_declaration.type_def = handle_type(inner_pair);
+                var_declaration.type_def = Some(handle_type(inner_pair));
             }
             Rule::identifier => {
                 var_declaration.identifier = handle_identifier(inner_pair);
@@ -297,25 +754,168 @@ pub fn handle_var_declaration(pair: Pair<'_, Rule>) -> VarDeclaration {
     var_declaration
 }
 
+pub fn handle_const_declaration(pair: Pair<'_, Rule>) -> ConstDeclaration {
+    let mut const_declaration = ConstDeclaration::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::doc_comment => push_doc_comment(&mut const_declaration.doc_comment, inner_pair),
+            Rule::access_modifier => {
+                const_declaration.access_modifier = handle_access_modifier(inner_pair);
+            }
+            Rule::r#type => const_declaration.type_def = handle_type(inner_pair),
+            Rule::identifier => const_declaration.identifier = handle_identifier(inner_pair),
+            Rule::expression => const_declaration.definition = handle_expression(inner_pair),
+            _ => {}
+        }
+    }
+
+    const_declaration
+}
+
+pub fn handle_parameter_list(pair: Pair<'_, Rule>) -> Vec<Parameter> {
+    pair.into_inner().map(handle_parameter).collect()
+}
+
+pub fn handle_parameter(pair: Pair<'_, Rule>) -> Parameter {
+    let mut parameter = Parameter::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::identifier => parameter.identifier = handle_identifier(inner_pair),
+            Rule::r#type => parameter.type_def = handle_type(inner_pair),
+            Rule::expression => parameter.default_value = Some(handle_expression(inner_pair)),
+            _ => {}
+        }
+    }
+
+    parameter
+}
+
+pub fn handle_function_declaration(pair: Pair<'_, Rule>) -> FunctionDeclaration {
+    let mut function_declaration = FunctionDeclaration::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::doc_comment => push_doc_comment(&mut function_declaration.doc_comment, inner_pair),
+            Rule::access_modifier => {
+                function_declaration.access_modifier = handle_access_modifier(inner_pair);
+            }
+            Rule::r#async => function_declaration.is_async = true,
+            Rule::identifier => function_declaration.identifier = handle_identifier(inner_pair),
+            Rule::type_argument => {
+                function_declaration.type_arguments = handle_type_argument(inner_pair);
+            }
+            Rule::parameter_list => {
+                function_declaration.parameter_list = handle_parameter_list(inner_pair);
+            }
+            Rule::r#type => function_declaration.return_type = handle_type(inner_pair),
+            Rule::block => function_declaration.block = handle_block(inner_pair),
+            _ => {}
+        }
+    }
+
+    function_declaration
+}
+
+pub fn handle_class_declaration(pair: Pair<'_, Rule>) -> ClassDeclaration {
+    let mut class_declaration = ClassDeclaration::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::doc_comment => push_doc_comment(&mut class_declaration.doc_comment, inner_pair),
+            Rule::access_modifier => {
+                class_declaration.access_modifier = handle_access_modifier(inner_pair);
+            }
+            Rule::r#static => class_declaration.is_static = true,
+            Rule::identifier => class_declaration.identifier = handle_identifier(inner_pair),
+            _ => {}
+        }
+    }
+
+    class_declaration
+}
+
+pub fn handle_interface_declaration(pair: Pair<'_, Rule>) -> InterfaceDeclaration {
+    let mut interface_declaration = InterfaceDeclaration::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::doc_comment => push_doc_comment(&mut interface_declaration.doc_comment, inner_pair),
+            Rule::access_modifier => {
+                interface_declaration.access_modifier = handle_access_modifier(inner_pair);
+            }
+            Rule::identifier => {
+                interface_declaration.identifier = handle_identifier(inner_pair);
+            }
+            _ => {}
+        }
+    }
+
+    interface_declaration
+}
+
+pub fn handle_enum_declaration(pair: Pair<'_, Rule>) -> EnumDeclaration {
+    let mut enum_declaration = EnumDeclaration::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::doc_comment => push_doc_comment(&mut enum_declaration.doc_comment, inner_pair),
+            Rule::access_modifier => {
+                enum_declaration.access_modifier = handle_access_modifier(inner_pair);
+            }
+            Rule::identifier => enum_declaration.identifier = handle_identifier(inner_pair),
+            Rule::enum_variant => enum_declaration.variants.push(handle_enum_variant(inner_pair)),
+            _ => {}
+        }
+    }
+
+    enum_declaration
+}
+
+pub fn handle_enum_variant(pair: Pair<'_, Rule>) -> EnumVariant {
+    let mut enum_variant = EnumVariant::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::identifier => enum_variant.identifier = handle_identifier(inner_pair),
+            Rule::r#type => enum_variant.payload_types.push(handle_type(inner_pair)),
+            _ => {}
+        }
+    }
+
+    enum_variant
+}
+
 pub fn handle_declaration(pair: Pair<'_, Rule>) -> Declaration {
     let mut declaration = Declaration::default();
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
             Rule::var_declaration => {
+                declaration.declaration_kind = DeclarationKind::VAR;
                 declaration.var_declaration = Some(handle_var_declaration(inner_pair));
             }
             Rule::const_declaration => {
                 declaration.declaration_kind = DeclarationKind::CONST;
+                declaration.const_declaration = Some(handle_const_declaration(inner_pair));
             }
             Rule::class_declaration => {
                 declaration.declaration_kind = DeclarationKind::CLASS;
+                declaration.class_declaration = Some(handle_class_declaration(inner_pair));
             }
             Rule::function_declaration => {
                 declaration.declaration_kind = DeclarationKind::FUNCTION;
+                declaration.function_declaration = Some(handle_function_declaration(inner_pair));
             }
             Rule::interface_declaration => {
                 declaration.declaration_kind = DeclarationKind::INTERFACE;
+                declaration.interface_declaration =
+                    Some(handle_interface_declaration(inner_pair));
+            }
+            Rule::enum_declaration => {
+                declaration.declaration_kind = DeclarationKind::ENUM;
+                declaration.enum_declaration = Some(handle_enum_declaration(inner_pair));
             }
             _ => {}
         }
@@ -324,4 +924,232 @@ pub fn handle_declaration(pair: Pair<'_, Rule>) -> Declaration {
     declaration
 }
 
-fn solve_expression(expression: Expression) {}
\ No newline at end of file
+pub fn handle_assignment(pair: Pair<'_, Rule>) -> Assignment {
+    let mut assignment = Assignment::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::identifier => assignment.identifier = handle_identifier(inner_pair),
+            Rule::index_expression => {
+                let index_expression = handle_index_expression(inner_pair);
+                assignment.identifier = index_expression.identifier;
+                assignment.indices = index_expression.indices;
+            }
+            Rule::definition => {
+                assignment.definition = handle_expression(inner_pair.into_inner().next().unwrap());
+            }
+            _ => {}
+        }
+    }
+
+    assignment
+}
+
+pub fn handle_block(pair: Pair<'_, Rule>) -> Block {
+    let mut block = Block::default();
+
+    for inner_pair in pair.into_inner() {
+        if let Rule::stmt = inner_pair.as_rule() {
+            block.statements.push(handle_statement(inner_pair));
+        }
+    }
+
+    block
+}
+
+pub fn handle_statement(pair: Pair<'_, Rule>) -> Statement {
+    let mut statement = Statement::default();
+    let mut is_awaited = false;
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::r#await => is_awaited = true,
+            Rule::expression => {
+                let expression = handle_expression(inner_pair);
+                if is_awaited {
+                    statement.statement_kind = StatementKind::ASYNCEXPRESSION;
+                    statement.async_expression = Some(expression);
+                } else {
+                    statement.statement_kind = StatementKind::EXPRESSION;
+                    statement.expression = Some(expression);
+                }
+            }
+            Rule::match_expression => {
+                statement.statement_kind = StatementKind::EXPRESSION;
+                statement.expression = Some(handle_operand(inner_pair));
+            }
+            Rule::const_declaration => {
+                statement.statement_kind = StatementKind::CONSTDECLARATION;
+                statement.const_declaration = Some(handle_const_declaration(inner_pair));
+            }
+            Rule::var_declaration => {
+                statement.statement_kind = StatementKind::VARDECLARATION;
+                statement.var_declaration = Some(handle_var_declaration(inner_pair));
+            }
+            Rule::block => {
+                statement.statement_kind = StatementKind::BLOCK;
+                statement.block = Some(handle_block(inner_pair));
+            }
+            Rule::if_stmt => {
+                statement.statement_kind = StatementKind::IFSTMT;
+                statement.if_statement = Some(handle_if_statement(inner_pair));
+            }
+            Rule::while_stmt => {
+                statement.statement_kind = StatementKind::WHILESTMT;
+                statement.while_statement = Some(handle_while_statement(inner_pair));
+            }
+            Rule::for_stmt => {
+                statement.statement_kind = StatementKind::FORSTMT;
+                statement.for_statement = Some(handle_for_statement(inner_pair));
+            }
+            Rule::return_stmt => {
+                statement.statement_kind = StatementKind::RETURNSTMT;
+                statement.return_statement = Some(handle_return_statement(inner_pair));
+            }
+            Rule::throw_stmt => {
+                statement.statement_kind = StatementKind::THROWSTMT;
+                statement.throw_statement = Some(ThrowStatement {
+                    expression: handle_expression(inner_pair.into_inner().next().unwrap()),
+                });
+            }
+            Rule::try_stmt => {
+                statement.statement_kind = StatementKind::TRYSTMT;
+                statement.try_statement = Some(handle_try_statement(inner_pair));
+            }
+            Rule::assignment => {
+                statement.statement_kind = StatementKind::ASSIGNMENT;
+                statement.assignment = Some(handle_assignment(inner_pair));
+            }
+            _ => {}
+        }
+    }
+
+    statement
+}
+
+pub fn handle_try_statement(pair: Pair<'_, Rule>) -> TryStatement {
+    let mut try_statement = TryStatement::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::block => try_statement.block = handle_block(inner_pair),
+            Rule::catch_clause => {
+                try_statement.catch_clauses.push(handle_catch_clause(inner_pair));
+            }
+            Rule::finally_clause => {
+                try_statement.finally_block =
+                    Some(handle_block(inner_pair.into_inner().next().unwrap()));
+            }
+            _ => {}
+        }
+    }
+
+    try_statement
+}
+
+pub fn handle_catch_clause(pair: Pair<'_, Rule>) -> CatchClause {
+    let mut catch_clause = CatchClause::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::identifier => catch_clause.identifier = handle_identifier(inner_pair),
+            Rule::r#type => catch_clause.type_def = Some(handle_type(inner_pair)),
+            Rule::block => catch_clause.block = handle_block(inner_pair),
+            _ => {}
+        }
+    }
+
+    catch_clause
+}
+
+pub fn handle_if_statement(pair: Pair<'_, Rule>) -> IfStatement {
+    let mut if_statement = IfStatement::default();
+    let mut has_body = false;
+    if_statement.if_statement_kind = IfStatementKind::OPEN;
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::expression => if_statement.expression = handle_expression(inner_pair),
+            Rule::block if !has_body => {
+                if_statement.block = handle_block(inner_pair);
+                has_body = true;
+            }
+            Rule::block => {
+                if_statement.else_statement = Some(handle_block(inner_pair));
+                if_statement.if_statement_kind = IfStatementKind::CLOSED;
+            }
+            Rule::if_stmt => {
+                if_statement.else_if_statement = Some(Box::new(handle_if_statement(inner_pair)));
+                if_statement.if_statement_kind = IfStatementKind::DOUBLE;
+            }
+            _ => {}
+        }
+    }
+
+    if_statement
+}
+
+pub fn handle_while_statement(pair: Pair<'_, Rule>) -> WhileStatement {
+    let mut while_statement = WhileStatement::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::expression => while_statement.expression = handle_expression(inner_pair),
+            Rule::block => while_statement.block = handle_block(inner_pair),
+            _ => {}
+        }
+    }
+
+    while_statement
+}
+
+pub fn handle_for_statement(pair: Pair<'_, Rule>) -> ForStatement {
+    let mut for_statement = ForStatement::default();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::var_declaration => {
+                for_statement.for_statement_kind = ForStatementKind::CONDITIONAL;
+                for_statement.variable = handle_var_declaration(inner_pair);
+            }
+            Rule::identifier => {
+                for_statement.for_statement_kind = ForStatementKind::ITERATOR;
+                for_statement.variable.identifier = handle_identifier(inner_pair);
+            }
+            Rule::expression => match for_statement.for_statement_kind {
+                ForStatementKind::CONDITIONAL => {
+                    for_statement.condition = handle_expression(inner_pair)
+                }
+                ForStatementKind::ITERATOR => {
+                    for_statement.iterable = handle_expression(inner_pair)
+                }
+            },
+            Rule::for_step => for_statement.step = Box::new(handle_for_step(inner_pair)),
+            Rule::block => for_statement.block = handle_block(inner_pair),
+            _ => {}
+        }
+    }
+
+    for_statement
+}
+
+/// The step of `for (init; condition; step)` is an assignment without `;` or an expression.
+pub fn handle_for_step(pair: Pair<'_, Rule>) -> Statement {
+    let mut statement = Statement::default();
+
+    if pair.clone().into_inner().any(|inner_pair| inner_pair.as_rule() == Rule::definition) {
+        statement.statement_kind = StatementKind::ASSIGNMENT;
+        statement.assignment = Some(handle_assignment(pair));
+    } else {
+        statement.statement_kind = StatementKind::EXPRESSION;
+        statement.expression = Some(handle_expression(pair.into_inner().next().unwrap()));
+    }
+
+    statement
+}
+
+pub fn handle_return_statement(pair: Pair<'_, Rule>) -> ReturnStatement {
+    ReturnStatement {
+        expression: pair.into_inner().next().map(handle_expression),
+    }
+}
@@ -0,0 +1,2130 @@
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+use crate::{
+    errors::{RuntimeError, RuntimeErrorKind},
+    modules::{Export, Module, ModuleResolver},
+    numeric::{apply_arithmetic, is_numeric, negate},
+    runtime::{
+        bind_type, compare_values, result_declaration, type_matches, values_equal, EnumValue, EventLoop,
+        FunctionValue, FutureState, FutureValue, Interrupt, SharedFuture, Value, Variable,
+    },
+    standard_library::{
+        array::{expect_arguments, ArrayModule},
+        error::ErrorModule,
+        future::FutureModule,
+        map::MapModule,
+        number::NumberModule,
+        string::StringModule,
+        NativeModule, NativeRegistry,
+    },
+    structure::{
+        AffixExpression, Argument, Assignment, BinaryExpression, Block, CatchClause, ConstDeclaration,
+        Declaration, DeclarationKind, EnumDeclaration, EnumVariant, ExecutionContext, Expression,
+        ExpressionKind, ForStatement, ForStatementKind, FunctionCall, FunctionDeclaration,
+        IfStatement, ImportedModule, IndexExpression, Literal, LiteralKind, MatchExpression,
+        MemberAccess, MemberAccessKind, Pattern, PatternKind, ScopeType, Statement, StatementKind,
+        StringSegmentKind, TryStatement, Type, UnaryExpression, UnaryExpressionKind, VarDeclaration,
+    },
+    tasks::{lower_body, Frame, Task, TaskStatement, TryStage},
+};
+
+type Context = Rc<RefCell<ExecutionContext>>;
+
+/// Calls nested deeper than this raise an `Error` instead of overflowing the Rust stack,
+/// see `INTERPRETER_STACK_SIZE` in main.rs
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+#[derive(Debug)]
+pub struct Interpreter {
+    pub modules: ModuleResolver,
+    pub natives: NativeRegistry,
+    pub global_context: Context,
+    pub event_loop: EventLoop,
+    /// Command-line arguments given after the program path
+    pub program_arguments: Vec<String>,
+    /// Set by `--allow-process`, lets programs read the environment and run commands
+    pub allow_process: bool,
+    /// Functions currently running, nested in one another
+    pub call_depth: usize,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter {
+            modules: ModuleResolver::default(),
+            natives: NativeRegistry::default(),
+            global_context: Interpreter::new_global_context(),
+            event_loop: EventLoop::default(),
+            program_arguments: Vec::new(),
+            allow_process: false,
+            call_depth: 0,
+        }
+    }
+}
+
+impl Interpreter {
+    pub fn new_global_context() -> Context {
+        Rc::new(RefCell::new(ExecutionContext {
+            scope_name: String::from("global"),
+            scope_type: ScopeType::GLOBAL,
+            scoped_enums: vec![Rc::new(result_declaration())],
+            ..Default::default()
+        }))
+    }
+
+    /// Imports are matched by their full path against the native modules first, any other
+    /// import names a `.ws` file.
+    pub fn execute_import(
+        &mut self,
+        imported_module: ImportedModule,
+        context: &Context,
+    ) -> Result<(), Interrupt> {
+        if imported_module.items.is_empty() && self.natives.find(&imported_module.full_path).is_some() {
+            import_native_module(imported_module, context);
+            return Ok(());
+        }
+
+        // `import std.io.{Console as Out};` is the same as `import std.io.Console as Out;`
+        let is_native = imported_module.items.iter().any(|item| {
+            let full_path = format!("{}.{}", imported_module.full_path, item.identifier);
+            self.natives.find(&full_path).is_some()
+        });
+        if is_native {
+            for item in &imported_module.items {
+                let native_module = ImportedModule {
+                    parent: imported_module.parent.clone(),
+                    name: item.identifier.clone(),
+                    full_path: format!("{}.{}", imported_module.full_path, item.identifier),
+                    alias: item.alias.clone(),
+                    items: Vec::new(),
+                };
+                self.check_native_module(&native_module)?;
+                import_native_module(native_module, context);
+            }
+            return Ok(());
+        }
+
+        let module = ModuleResolver::import(self, &imported_module)?;
+
+        if imported_module.items.is_empty() {
+            let local_name = imported_module.local_name();
+            let mut context = context.borrow_mut();
+            if context.scoped_modules.contains_key(local_name) {
+                return Err(RuntimeError {
+                    identifier: Some(local_name.to_string()),
+                    ..RuntimeError::import_error(&format!(
+                        "A module named `{local_name}` is already imported"
+                    ))
+                }
+                .into());
+            }
+            context.scoped_modules.insert(local_name.to_string(), module);
+            return Ok(());
+        }
+
+        for item in &imported_module.items {
+            let local_name = item.local_name();
+            match module.get_export(&item.identifier)? {
+                Export::VALUE(variable) => {
+                    self.declare(context, local_name, variable.value, None, true)?;
+                }
+                // Values of an enum carry its name, so it keeps it in every file
+                Export::ENUM(_) if item.alias.is_some() => {
+                    return Err(RuntimeError {
+                        identifier: Some(item.identifier.clone()),
+                        ..RuntimeError::import_error(&format!(
+                            "The enum `{}` cannot be renamed on import",
+                            item.identifier
+                        ))
+                    }
+                    .into());
+                }
+                Export::ENUM(enum_declaration) => {
+                    context.borrow_mut().scoped_enums.push(enum_declaration);
+                }
+                Export::CLASS(mut class_declaration) => {
+                    class_declaration.identifier = local_name.to_string();
+                    context.borrow_mut().scoped_classes.push(class_declaration);
+                }
+                Export::INTERFACE(mut interface_declaration) => {
+                    interface_declaration.identifier = local_name.to_string();
+                    context.borrow_mut().scoped_interfaces.push(interface_declaration);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_native_module(&self, imported_module: &ImportedModule) -> Result<(), Interrupt> {
+        if self.natives.find(&imported_module.full_path).is_none() {
+            let error = RuntimeError {
+                identifier: Some(imported_module.name.clone()),
+                ..RuntimeError::import_error(&format!(
+                    "Cannot find module `{}`",
+                    imported_module.full_path
+                ))
+            };
+            return Err(match self.natives.suggest(&imported_module.full_path) {
+                Some(native_path) => error.with_suggestion(&format!("Did you mean `{native_path}`?")),
+                None => error,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    pub fn execute_declaration(
+        &mut self,
+        declaration: &Declaration,
+        context: &Context,
+    ) -> Result<(), Interrupt> {
+        if let Some(identifier) = declaration.public_identifier() {
+            context.borrow_mut().public_names.insert(identifier.to_string());
+        }
+
+        match declaration.declaration_kind {
+            DeclarationKind::VAR => {
+                self.execute_var_declaration(declaration.var_declaration.as_ref().unwrap(), context)
+            }
+            DeclarationKind::CONST => self.execute_const_declaration(
+                declaration.const_declaration.as_ref().unwrap(),
+                context,
+            ),
+            DeclarationKind::FUNCTION => {
+                self.declare_function(declaration.function_declaration.as_ref().unwrap(), context)
+            }
+            DeclarationKind::CLASS => {
+                let class_declaration = declaration.class_declaration.clone().unwrap();
+                context.borrow_mut().scoped_classes.push(class_declaration);
+                Ok(())
+            }
+            DeclarationKind::ENUM => {
+                self.declare_enum(declaration.enum_declaration.as_ref().unwrap(), context)
+            }
+            DeclarationKind::INTERFACE => {
+                let interface_declaration = declaration.interface_declaration.clone().unwrap();
+                context
+                    .borrow_mut()
+                    .scoped_interfaces
+                    .push(interface_declaration);
+                Ok(())
+            }
+        }
+    }
+
+    fn execute_var_declaration(
+        &mut self,
+        var_declaration: &VarDeclaration,
+        context: &Context,
+    ) -> Result<(), Interrupt> {
+        let value = match &var_declaration.definition {
+            Some(definition) => self.solve_expression(definition, context)?,
+            None => Value::NULL,
+        };
+
+        self.declare(
+            context,
+            &var_declaration.identifier,
+            value,
+            var_declaration.type_def.as_ref(),
+            false,
+        )?;
+        Ok(())
+    }
+
+    fn execute_const_declaration(
+        &mut self,
+        const_declaration: &ConstDeclaration,
+        context: &Context,
+    ) -> Result<(), Interrupt> {
+        let value = self.solve_expression(&const_declaration.definition, context)?;
+
+        self.declare(
+            context,
+            &const_declaration.identifier,
+            value,
+            Some(&const_declaration.type_def),
+            true,
+        )?;
+        Ok(())
+    }
+
+    fn declare_enum(
+        &mut self,
+        enum_declaration: &EnumDeclaration,
+        context: &Context,
+    ) -> Result<(), Interrupt> {
+        let identifier = &enum_declaration.identifier;
+        let is_declared = context
+            .borrow()
+            .scoped_enums
+            .iter()
+            .any(|declared| &declared.identifier == identifier);
+        if is_declared {
+            return Err(RuntimeError::type_error(&format!(
+                "Enum `{identifier}` is already declared in this scope"
+            ))
+            .into());
+        }
+
+        for (position, variant) in enum_declaration.variants.iter().enumerate() {
+            let is_repeated = enum_declaration.variants[..position]
+                .iter()
+                .any(|previous| previous.identifier == variant.identifier);
+            if is_repeated {
+                return Err(RuntimeError::type_error(&format!(
+                    "Variant `{identifier}::{}` is declared more than once",
+                    variant.identifier
+                ))
+                .into());
+            }
+        }
+
+        context
+            .borrow_mut()
+            .scoped_enums
+            .push(Rc::new(enum_declaration.clone()));
+        Ok(())
+    }
+
+    fn declare_function(
+        &mut self,
+        function_declaration: &FunctionDeclaration,
+        context: &Context,
+    ) -> Result<(), Interrupt> {
+        let return_type = &function_declaration.return_type;
+        if function_declaration.is_async && return_type.type_identifier != "Future" {
+            let identifier = &function_declaration.identifier;
+            let inner = match return_type.type_identifier.is_empty() {
+                true => String::from("void"),
+                false => return_type.to_string(),
+            };
+            return Err(RuntimeError::type_error(&format!(
+                "`async function {identifier}` should return `Future<{inner}>`, found `{inner}`"
+            ))
+            .with_suggestion(&format!(
+                "Declare it as `async function {identifier}(...) -> Future<{inner}>`"
+            ))
+            .into());
+        }
+
+        let function = Value::FUNCTION(Rc::new(FunctionValue {
+            declaration: function_declaration.clone(),
+            closure: context.clone(),
+            task_body: function_declaration
+                .is_async
+                .then(|| lower_body(&function_declaration.block.statements)),
+        }));
+
+        self.declare(
+            context,
+            &function_declaration.identifier,
+            function,
+            None,
+            true,
+        )?;
+        Ok(())
+    }
+
+    fn declare(
+        &mut self,
+        context: &Context,
+        identifier: &str,
+        value: Value,
+        type_def: Option<&Type>,
+        is_constant: bool,
+    ) -> Result<(), RuntimeError> {
+        if context.borrow().scoped_variables.contains_key(identifier) {
+            return Err(RuntimeError::type_error(&format!(
+                "`{identifier}` is already declared in this scope"
+            )));
+        }
+
+        let value = match type_def {
+            Some(type_def) => {
+                check_type(&value, type_def, identifier)?;
+                bind_type(value, type_def)
+            }
+            None => value,
+        };
+
+        context.borrow_mut().scoped_variables.insert(
+            identifier.to_string(),
+            Variable {
+                value,
+                type_def: type_def.cloned(),
+                is_constant,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn execute_assignment(
+        &mut self,
+        assignment: &Assignment,
+        context: &Context,
+    ) -> Result<(), Interrupt> {
+        let value = self.solve_expression(&assignment.definition, context)?;
+
+        if assignment.indices.is_empty() {
+            return Ok(self.assign(context, &assignment.identifier, value)?);
+        }
+
+        let mut target = self.solve_identifier(&assignment.identifier, context)?;
+        let (last, rest) = assignment.indices.split_last().unwrap();
+        for index in rest {
+            let index = self.solve_expression(index, context)?;
+            target = index_value(&target, &index)?;
+        }
+
+        let index = self.solve_expression(last, context)?;
+        Ok(set_index_value(&target, index, value)?)
+    }
+
+    fn assign(
+        &mut self,
+        context: &Context,
+        identifier: &str,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        let mut current = Some(context.clone());
+
+        while let Some(scope) = current {
+            if let Some(variable) = scope.borrow_mut().scoped_variables.get_mut(identifier) {
+                if variable.is_constant {
+                    return Err(RuntimeError::type_error(&format!(
+                        "Cannot assign to constant `{identifier}`"
+                    )));
+                }
+                variable.value = match &variable.type_def {
+                    Some(type_def) => {
+                        check_type(&value, type_def, identifier)?;
+                        bind_type(value, type_def)
+                    }
+                    None => value,
+                };
+                return Ok(());
+            }
+            current = scope.borrow().parent.clone();
+        }
+
+        Err(RuntimeError::undefined(identifier))
+    }
+
+    /// Runs `block` in a fresh scope nested in `context`.
+    pub fn execute_block(&mut self, block: &Block, context: &Context) -> Result<(), Interrupt> {
+        let block_context = ExecutionContext::new_child(context, "block", ScopeType::BLOCK);
+        self.execute_statements(&block.statements, &block_context)
+    }
+
+    pub fn execute_statements(
+        &mut self,
+        statements: &[Statement],
+        context: &Context,
+    ) -> Result<(), Interrupt> {
+        for statement in statements {
+            self.execute_statement(statement, context)?;
+        }
+        Ok(())
+    }
+
+    pub fn execute_statement(
+        &mut self,
+        statement: &Statement,
+        context: &Context,
+    ) -> Result<(), Interrupt> {
+        match statement.statement_kind {
+            StatementKind::EXPRESSION => {
+                self.solve_expression(statement.expression.as_ref().unwrap(), context)?;
+                Ok(())
+            }
+            StatementKind::ASYNCEXPRESSION => {
+                let value =
+                    self.solve_expression(statement.async_expression.as_ref().unwrap(), context)?;
+                self.await_value(value)?;
+                Ok(())
+            }
+            StatementKind::CONSTDECLARATION => self
+                .execute_const_declaration(statement.const_declaration.as_ref().unwrap(), context),
+            StatementKind::VARDECLARATION => {
+                self.execute_var_declaration(statement.var_declaration.as_ref().unwrap(), context)
+            }
+            StatementKind::BLOCK => self.execute_block(statement.block.as_ref().unwrap(), context),
+            StatementKind::IFSTMT => {
+                self.execute_if_statement(statement.if_statement.as_ref().unwrap(), context)
+            }
+            StatementKind::WHILESTMT => {
+                let while_statement = statement.while_statement.as_ref().unwrap();
+                while self.solve_condition(&while_statement.expression, context)? {
+                    self.execute_block(&while_statement.block, context)?;
+                }
+                Ok(())
+            }
+            StatementKind::FORSTMT => {
+                self.execute_for_statement(statement.for_statement.as_ref().unwrap(), context)
+            }
+            StatementKind::RETURNSTMT => {
+                let value = match &statement.return_statement.as_ref().unwrap().expression {
+                    Some(expression) => self.solve_expression(expression, context)?,
+                    None => Value::NULL,
+                };
+                Err(Interrupt::RETURN(value))
+            }
+            StatementKind::THROWSTMT => {
+                let expression = &statement.throw_statement.as_ref().unwrap().expression;
+                match self.solve_expression(expression, context)? {
+                    Value::ERROR(error) => {
+                        // The stack is rebuilt from the `throw`, also when an error is rethrown
+                        let mut error = (*error).clone();
+                        error.stack.clear();
+                        Err(Interrupt::ERROR(error))
+                    }
+                    value => Err(RuntimeError::type_error(&format!(
+                        "Only `Error` values can be thrown, found `{}`",
+                        value.type_name()
+                    ))
+                    .into()),
+                }
+            }
+            StatementKind::TRYSTMT => {
+                self.execute_try_statement(statement.try_statement.as_ref().unwrap(), context)
+            }
+            StatementKind::ASSIGNMENT => {
+                self.execute_assignment(statement.assignment.as_ref().unwrap(), context)
+            }
+        }
+    }
+
+    fn execute_try_statement(
+        &mut self,
+        try_statement: &TryStatement,
+        context: &Context,
+    ) -> Result<(), Interrupt> {
+        let result = match self.execute_block(&try_statement.block, context) {
+            Err(Interrupt::ERROR(error)) => {
+                match self.select_catch_clause(&try_statement.catch_clauses, error, context) {
+                    Ok((position, catch_context)) => {
+                        let catch_clause = &try_statement.catch_clauses[position];
+                        self.execute_statements(&catch_clause.block.statements, &catch_context)
+                    }
+                    Err(interrupt) => Err(interrupt),
+                }
+            }
+            result => result,
+        };
+
+        // `finally` always runs, and its own interrupt replaces the pending one
+        match &try_statement.finally_block {
+            Some(finally_block) => {
+                self.execute_block(finally_block, context)?;
+                result
+            }
+            None => result,
+        }
+    }
+
+    /// The first clause catching `error`, with the scope its block runs in. The error goes
+    /// on unwinding when no clause catches it.
+    fn select_catch_clause(
+        &mut self,
+        catch_clauses: &[CatchClause],
+        error: RuntimeError,
+        context: &Context,
+    ) -> Result<(usize, Context), Interrupt> {
+        for (position, catch_clause) in catch_clauses.iter().enumerate() {
+            if let Some(type_def) = &catch_clause.type_def {
+                if RuntimeErrorKind::from_name(&type_def.type_identifier).is_none() {
+                    return Err(RuntimeError::type_error(&format!(
+                        "`{type_def}` is not an error class and cannot be caught"
+                    ))
+                    .into());
+                }
+                if !error.is_instance_of(&type_def.type_identifier) {
+                    continue;
+                }
+            }
+
+            let catch_context = ExecutionContext::new_child(context, "catch", ScopeType::BLOCK);
+            self.declare(
+                &catch_context,
+                &catch_clause.identifier,
+                Value::ERROR(Rc::new(error)),
+                None,
+                false,
+            )?;
+            return Ok((position, catch_context));
+        }
+
+        Err(Interrupt::ERROR(error))
+    }
+
+    fn execute_if_statement(
+        &mut self,
+        if_statement: &IfStatement,
+        context: &Context,
+    ) -> Result<(), Interrupt> {
+        if self.solve_condition(&if_statement.expression, context)? {
+            self.execute_block(&if_statement.block, context)
+        } else if let Some(else_if_statement) = &if_statement.else_if_statement {
+            self.execute_if_statement(else_if_statement, context)
+        } else if let Some(else_statement) = &if_statement.else_statement {
+            self.execute_block(else_statement, context)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn execute_for_statement(
+        &mut self,
+        for_statement: &ForStatement,
+        context: &Context,
+    ) -> Result<(), Interrupt> {
+        match for_statement.for_statement_kind {
+            ForStatementKind::ITERATOR => {
+                let iterable = self.solve_expression(&for_statement.iterable, context)?;
+                let elements = iteration_elements(&iterable)?;
+
+                for element in elements {
+                    let loop_context =
+                        ExecutionContext::new_child(context, "for", ScopeType::BLOCK);
+                    self.declare(
+                        &loop_context,
+                        &for_statement.variable.identifier,
+                        element,
+                        None,
+                        false,
+                    )?;
+                    self.execute_block(&for_statement.block, &loop_context)?;
+                }
+                Ok(())
+            }
+            ForStatementKind::CONDITIONAL => {
+                let loop_context = ExecutionContext::new_child(context, "for", ScopeType::BLOCK);
+                self.execute_var_declaration(&for_statement.variable, &loop_context)?;
+
+                while self.solve_condition(&for_statement.condition, &loop_context)? {
+                    self.execute_block(&for_statement.block, &loop_context)?;
+                    self.execute_statement(&for_statement.step, &loop_context)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn solve_condition(
+        &mut self,
+        expression: &Expression,
+        context: &Context,
+    ) -> Result<bool, Interrupt> {
+        match self.solve_expression(expression, context)? {
+            Value::BOOL(value) => Ok(value),
+            value => Err(RuntimeError::type_error(&format!(
+                "Expected a `bool` condition, found `{}`",
+                value.type_name()
+            ))
+            .into()),
+        }
+    }
+
+    pub fn solve_expression(
+        &mut self,
+        expression: &Expression,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        match expression.expression_kind {
+            ExpressionKind::UNARY => {
+                self.solve_unary_expression(expression.unary_expression.as_ref().unwrap(), context)
+            }
+            ExpressionKind::BINARY => self
+                .solve_binary_expression(expression.binary_expression.as_ref().unwrap(), context),
+            ExpressionKind::MEMBERACCESS => {
+                self.solve_member_access(expression.member_access.as_ref().unwrap(), context)
+            }
+            ExpressionKind::INDEX => {
+                self.solve_index_expression(expression.index_expression.as_ref().unwrap(), context)
+            }
+            ExpressionKind::PREFIX => self
+                .solve_prefix_expression(expression.prefix_expression.as_ref().unwrap(), context),
+            ExpressionKind::POSTFIX => {
+                self.solve_update(expression.postfix_expression.as_ref().unwrap(), false, context)
+            }
+            ExpressionKind::CALL => {
+                let call_expression = expression.call_expression.as_ref().unwrap();
+                let callee = self.solve_expression(&call_expression.callee, context)?;
+                let arguments = self.solve_call_arguments(&call_expression.args_list, context)?;
+                let result = self.call_with_arguments(&callee, arguments);
+                with_frame(result, || match &callee {
+                    Value::FUNCTION(function) => format!(
+                        "at {}() called on line {}",
+                        function.declaration.identifier, call_expression.line
+                    ),
+                    _ => format!("at <expression>() called on line {}", call_expression.line),
+                })
+            }
+        }
+    }
+
+    fn solve_prefix_expression(
+        &mut self,
+        prefix_expression: &AffixExpression,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        match prefix_expression.operator.as_str() {
+            "++" | "--" => self.solve_update(prefix_expression, true, context),
+            "!" => match self.solve_expression(&prefix_expression.operand, context)? {
+                Value::BOOL(value) => Ok(Value::BOOL(!value)),
+                value => Err(RuntimeError::type_error(&format!(
+                    "Operator `!` expects a `bool`, found `{}`",
+                    value.type_name()
+                ))
+                .into()),
+            },
+            _ => {
+                let value = self.solve_expression(&prefix_expression.operand, context)?;
+                Ok(negate(&value)?)
+            }
+        }
+    }
+
+    /// `++x` and `--x` yield the updated value, `x++` and `x--` the previous one. The
+    /// target and its indices are evaluated once.
+    fn solve_update(
+        &mut self,
+        affix_expression: &AffixExpression,
+        is_prefix: bool,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        let operator = affix_expression.operator.as_str();
+        let operand = &affix_expression.operand;
+        let (identifier, index_expression) = match &operand.expression_kind {
+            ExpressionKind::UNARY if operand.as_identifier().is_some() => {
+                (operand.as_identifier().unwrap(), None)
+            }
+            ExpressionKind::INDEX => {
+                let index_expression = operand.index_expression.as_ref().unwrap();
+                (index_expression.identifier.as_str(), Some(index_expression))
+            }
+            _ => {
+                return Err(RuntimeError::type_error(&format!(
+                    "Operator `{operator}` can only be applied to a variable or an indexed element"
+                ))
+                .into())
+            }
+        };
+
+        let update = |current: &Value| match current {
+            Value::INT(_) | Value::FLOAT(_) => {
+                apply_arithmetic(&operator[..1], current, &Value::INT(1))
+            }
+            _ => Err(RuntimeError::type_error(&format!(
+                "Operator `{operator}` expects a number, found `{}`",
+                current.type_name()
+            ))),
+        };
+
+        let indices = index_expression.map_or(&[][..], |index| &index.indices[..]);
+        let (current, updated) = match indices.split_last() {
+            None => {
+                let current = self.solve_identifier(identifier, context)?;
+                let updated = update(&current)?;
+                self.assign(context, identifier, updated.clone())?;
+                (current, updated)
+            }
+            Some((last, rest)) => {
+                let mut target = self.solve_index_base(index_expression.unwrap(), context)?;
+                for index in rest {
+                    let index = self.solve_expression(index, context)?;
+                    target = index_value(&target, &index)?;
+                }
+                let index = self.solve_expression(last, context)?;
+                let current = index_value(&target, &index)?;
+                let updated = update(&current)?;
+                set_index_value(&target, index, updated.clone())?;
+                (current, updated)
+            }
+        };
+
+        Ok(if is_prefix { updated } else { current })
+    }
+
+    fn solve_unary_expression(
+        &mut self,
+        unary_expression: &UnaryExpression,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        match unary_expression.kind {
+            UnaryExpressionKind::IDENTIFIER => {
+                Ok(self
+                    .solve_identifier(unary_expression.string_value.as_ref().unwrap(), context)?)
+            }
+            UnaryExpressionKind::LITERAL => {
+                self.solve_literal(unary_expression.literal.as_ref().unwrap(), context)
+            }
+            UnaryExpressionKind::FUNCTIONCALL => {
+                self.solve_function_call(unary_expression.function_call.as_ref().unwrap(), context)
+            }
+            UnaryExpressionKind::MATCH => self.solve_match_expression(
+                unary_expression.match_expression.as_ref().unwrap(),
+                context,
+            ),
+            UnaryExpressionKind::AWAIT => {
+                let value =
+                    self.solve_expression(unary_expression.expression.as_ref().unwrap(), context)?;
+                self.await_value(value)
+            }
+            UnaryExpressionKind::LAMBDA => Ok(Value::FUNCTION(Rc::new(FunctionValue {
+                declaration: unary_expression.lambda.clone().unwrap(),
+                closure: context.clone(),
+                task_body: None,
+            }))),
+            UnaryExpressionKind::EXPRESSION => {
+                self.solve_expression(unary_expression.expression.as_ref().unwrap(), context)
+            }
+        }
+    }
+
+    fn solve_identifier(
+        &mut self,
+        identifier: &str,
+        context: &Context,
+    ) -> Result<Value, RuntimeError> {
+        match context.borrow().lookup(identifier) {
+            Some(variable) => Ok(variable.value),
+            None => Err(RuntimeError::undefined(identifier)),
+        }
+    }
+
+    fn solve_literal(&mut self, literal: &Literal, context: &Context) -> Result<Value, Interrupt> {
+        match literal.literal_kind {
+            LiteralKind::NUMBER | LiteralKind::FLOAT => Ok(literal.number.clone().unwrap()),
+            LiteralKind::STRING => {
+                let mut text = String::new();
+                for segment in &literal.segments {
+                    match segment.segment_kind {
+                        StringSegmentKind::TEXT => text.push_str(&segment.text),
+                        StringSegmentKind::INTERPOLATION => {
+                            let value =
+                                self.solve_expression(segment.expression.as_ref().unwrap(), context)?;
+                            text.push_str(&value.to_string());
+                        }
+                    }
+                }
+                Ok(Value::STRING(text))
+            }
+            LiteralKind::BOOLEAN => Ok(Value::BOOL(literal.value == "true")),
+            LiteralKind::NULL => Ok(Value::NULL),
+            LiteralKind::ARRAY => {
+                let mut elements = Vec::with_capacity(literal.elements.len());
+                for element in &literal.elements {
+                    elements.push(self.solve_expression(element, context)?);
+                }
+                Ok(Value::new_array(elements))
+            }
+            LiteralKind::MAP => {
+                let mut entries = Vec::with_capacity(literal.entries.len());
+                for entry in &literal.entries {
+                    let key = self.solve_expression(&entry.key, context)?;
+                    let value = self.solve_expression(&entry.value, context)?;
+                    entries.push((key, value));
+                }
+                Ok(Value::new_map(entries)?)
+            }
+        }
+    }
+
+    fn solve_binary_expression(
+        &mut self,
+        binary_expression: &BinaryExpression,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        let first = self.solve_expression(&binary_expression.first_term, context)?;
+        let operator = binary_expression.operator.as_str();
+
+        // `&&` and `||` only evaluate the second term when they have to
+        if let ("&&" | "||", Value::BOOL(value)) = (operator, &first) {
+            if (operator == "&&") != *value {
+                return Ok(Value::BOOL(*value));
+            }
+        }
+
+        let second = self.solve_expression(&binary_expression.second_term, context)?;
+        Ok(apply_binary_operator(operator, &first, &second)?)
+    }
+
+    fn solve_index_expression(
+        &mut self,
+        index_expression: &IndexExpression,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        let mut value = self.solve_index_base(index_expression, context)?;
+
+        for index in &index_expression.indices {
+            let index = self.solve_expression(index, context)?;
+            value = index_value(&value, &index)?;
+        }
+
+        Ok(value)
+    }
+
+    fn solve_index_base(
+        &mut self,
+        index_expression: &IndexExpression,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        match &index_expression.object {
+            Some(object) => self.solve_expression(object, context),
+            None => Ok(self.solve_identifier(&index_expression.identifier, context)?),
+        }
+    }
+
+    fn solve_arguments(
+        &mut self,
+        args_list: &[Argument],
+        context: &Context,
+    ) -> Result<Vec<Value>, Interrupt> {
+        let mut arguments = Vec::with_capacity(args_list.len());
+
+        // Only declared functions have parameter names to match against
+        for argument in args_list {
+            if let Some(identifier) = &argument.identifier {
+                return Err(RuntimeError::type_error(&format!(
+                    "Named argument `{identifier}` can only be passed to a function"
+                ))
+                .into());
+            }
+            arguments.push(self.solve_expression(&argument.expression, context)?);
+        }
+
+        Ok(arguments)
+    }
+
+    fn solve_call_arguments(
+        &mut self,
+        args_list: &[Argument],
+        context: &Context,
+    ) -> Result<Vec<(Option<String>, Value)>, Interrupt> {
+        let mut arguments = Vec::with_capacity(args_list.len());
+
+        for argument in args_list {
+            let value = self.solve_expression(&argument.expression, context)?;
+            arguments.push((argument.identifier.clone(), value));
+        }
+
+        Ok(arguments)
+    }
+
+    fn solve_function_call(
+        &mut self,
+        function_call: &FunctionCall,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        let identifier = &function_call.identifier;
+        let function = match self.solve_identifier(identifier, context) {
+            Ok(function) => function,
+            Err(error) => return self.solve_builtin_call(function_call, error, context),
+        };
+        let arguments = self.solve_call_arguments(&function_call.args_list, context)?;
+
+        let result = self.call_with_arguments(&function, arguments);
+        with_frame(result, || format!("at {identifier}() called on line {}", function_call.line))
+    }
+
+    /// Functions that are available everywhere unless a declaration shadows them.
+    fn solve_builtin_call(
+        &mut self,
+        function_call: &FunctionCall,
+        error: RuntimeError,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        if let Some(kind) = RuntimeErrorKind::from_name(&function_call.identifier) {
+            return self.construct_error(kind, function_call, context);
+        }
+
+        match function_call.identifier.as_str() {
+            "sleep" => {
+                let arguments = self.solve_arguments(&function_call.args_list, context)?;
+                Ok(FutureModule::sleep(self, arguments)?)
+            }
+            _ => Err(error.into()),
+        }
+    }
+
+    /// `Error("message")`, `TypeError("message")`... build error values for `throw`.
+    fn construct_error(
+        &mut self,
+        kind: RuntimeErrorKind,
+        function_call: &FunctionCall,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        let arguments = self.solve_arguments(&function_call.args_list, context)?;
+        expect_arguments(&function_call.identifier, &arguments, 0, 1)?;
+
+        let message = match arguments.first() {
+            Some(Value::STRING(message)) => message.clone(),
+            Some(value) => {
+                return Err(RuntimeError::type_error(&format!(
+                    "`{}` expects a `string` message, found `{}`",
+                    function_call.identifier,
+                    value.type_name()
+                ))
+                .into())
+            }
+            None => String::new(),
+        };
+
+        Ok(Value::ERROR(Rc::new(RuntimeError::new(kind, &message))))
+    }
+
+    pub fn call_function(
+        &mut self,
+        function: &Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt> {
+        let arguments = arguments.into_iter().map(|argument| (None, argument)).collect();
+        let result = self.call_with_arguments(function, arguments);
+        // Callbacks of native functions, the frame of the native call holds the line
+        with_frame(result, || match function {
+            Value::FUNCTION(function) => format!("at {}()", function.declaration.identifier),
+            _ => String::from("at <callback>()"),
+        })
+    }
+
+    /// Calls a function with positional arguments followed by named ones.
+    pub fn call_with_arguments(
+        &mut self,
+        function: &Value,
+        arguments: Vec<(Option<String>, Value)>,
+    ) -> Result<Value, Interrupt> {
+        let function = match function {
+            Value::FUNCTION(function) => function.clone(),
+            _ => {
+                return Err(RuntimeError::type_error(&format!(
+                    "A value of type `{}` is not callable",
+                    function.type_name()
+                ))
+                .into())
+            }
+        };
+        let arguments = bind_arguments(&function.declaration, arguments)?;
+
+        // The task starts once the current one awaits, or once the script is done
+        if function.declaration.is_async {
+            let function_context = self.bind_parameters(&function, arguments)?;
+            let task = Task::new(function, function_context);
+            let future = Rc::new(RefCell::new(FutureValue::pending(task)));
+            self.event_loop.ready.push_back(future.clone());
+            return Ok(Value::FUTURE(future));
+        }
+
+        self.invoke_function(&function, arguments)
+    }
+
+    /// `None` arguments were left out of the call and take the parameter's default value.
+    fn invoke_function(
+        &mut self,
+        function: &FunctionValue,
+        arguments: Vec<Option<Value>>,
+    ) -> Result<Value, Interrupt> {
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::ERROR,
+                &format!("Maximum call depth of {MAX_CALL_DEPTH} exceeded"),
+            )
+            .with_suggestion("Check for a recursion that never reaches its base case")
+            .into());
+        }
+
+        self.call_depth += 1;
+        let result = self.run_function_body(function, arguments);
+        self.call_depth -= 1;
+        result
+    }
+
+    fn run_function_body(
+        &mut self,
+        function: &FunctionValue,
+        arguments: Vec<Option<Value>>,
+    ) -> Result<Value, Interrupt> {
+        let function_context = self.bind_parameters(function, arguments)?;
+
+        let statements = &function.declaration.block.statements;
+        let value = match self.execute_statements(statements, &function_context) {
+            Ok(()) => Value::NULL,
+            Err(Interrupt::RETURN(value)) => value,
+            Err(error) => return Err(error),
+        };
+
+        Ok(check_return_value(&function.declaration, value)?)
+    }
+
+    /// The scope a call runs in, with its parameters declared.
+    fn bind_parameters(
+        &mut self,
+        function: &FunctionValue,
+        arguments: Vec<Option<Value>>,
+    ) -> Result<Context, Interrupt> {
+        let declaration = &function.declaration;
+        let function_context = ExecutionContext::new_child(
+            &function.closure,
+            &declaration.identifier,
+            ScopeType::FUNCTIONDECLARATION,
+        );
+        for (parameter, argument) in declaration.parameter_list.iter().zip(arguments) {
+            let argument = match argument {
+                Some(argument) => argument,
+                None => {
+                    let default_value = parameter.default_value.as_ref().unwrap();
+                    self.solve_expression(default_value, &function_context)?
+                }
+            };
+            self.declare(
+                &function_context,
+                &parameter.identifier,
+                argument,
+                Some(&parameter.type_def),
+                false,
+            )?;
+        }
+
+        Ok(function_context)
+    }
+
+    /// Runs a ready task until it finishes or awaits a future that has not settled yet.
+    fn resume_task(&mut self, future: &SharedFuture) {
+        let mut task = match future.borrow_mut().task.take() {
+            Some(task) => task,
+            None => return,
+        };
+        future.borrow_mut().state = FutureState::RUNNING;
+
+        self.call_depth += 1;
+        let result = self.run_task(future, &mut task);
+        self.call_depth -= 1;
+
+        let declaration = &task.function.declaration;
+        let result = match result {
+            Some(result) => result.and_then(|value| check_return_value(declaration, value)),
+            None => {
+                future.borrow_mut().task = Some(task);
+                return;
+            }
+        };
+
+        let state = match result {
+            Ok(value) => FutureState::RESOLVED(value),
+            Err(mut error) => {
+                error.stack.push(format!("in async {}()", declaration.identifier));
+                self.event_loop.rejected.push(future.clone());
+                FutureState::REJECTED(error)
+            }
+        };
+        self.settle(future, state);
+    }
+
+    /// Runs the statements of a task until it returns, fails or awaits a future that has
+    /// not settled yet, `None` in that last case.
+    fn run_task(
+        &mut self,
+        future: &SharedFuture,
+        task: &mut Task,
+    ) -> Option<Result<Value, RuntimeError>> {
+        let mut result = match task.waiting.take() {
+            Some((awaited, identifier, context)) => settled_value(&awaited)
+                .and_then(|value| Ok(self.declare(&context, &identifier, value, None, false)?)),
+            None => Ok(()),
+        };
+
+        loop {
+            if let Err(interrupt) = result {
+                if let Some(outcome) = self.unwind_task(task, interrupt) {
+                    return Some(outcome);
+                }
+            }
+            if task.waiting.is_some() {
+                return None;
+            }
+            if task.frames.is_empty() {
+                return Some(Ok(Value::NULL));
+            }
+            result = self.step_task(future, task);
+        }
+    }
+
+    /// Runs the next statement of a task, or moves its innermost loop or `try` on.
+    fn step_task(&mut self, future: &SharedFuture, task: &mut Task) -> Result<(), Interrupt> {
+        match task.frames.pop().unwrap() {
+            Frame::BLOCK {
+                statements,
+                position,
+                context,
+            } => {
+                if position == statements.len() {
+                    return Ok(());
+                }
+                task.frames.push(Frame::BLOCK {
+                    statements: statements.clone(),
+                    position: position + 1,
+                    context: context.clone(),
+                });
+                self.run_task_statement(&statements[position], &context, future, task)
+            }
+            Frame::WHILE { node, context } => {
+                if self.solve_condition(&node.condition, &context)? {
+                    let statements = node.block.clone();
+                    task.frames.push(Frame::WHILE {
+                        node,
+                        context: context.clone(),
+                    });
+                    task.push_block(statements, &context);
+                }
+                Ok(())
+            }
+            Frame::FORIN {
+                node,
+                elements,
+                position,
+                context,
+            } => {
+                let element = match elements.get(position) {
+                    Some(element) => element.clone(),
+                    None => return Ok(()),
+                };
+                let loop_context = ExecutionContext::new_child(&context, "for", ScopeType::BLOCK);
+                self.declare(&loop_context, &node.identifier, element, None, false)?;
+                let statements = node.block.clone();
+                task.frames.push(Frame::FORIN {
+                    node,
+                    elements,
+                    position: position + 1,
+                    context,
+                });
+                task.push_block(statements, &loop_context);
+                Ok(())
+            }
+            // `finally` is done, the interrupt it ran for goes on unwinding
+            Frame::TRY {
+                stage: TryStage::FINALLY(pending),
+                ..
+            } => match pending {
+                Some(interrupt) => Err(interrupt),
+                None => Ok(()),
+            },
+            Frame::TRY { node, context, .. } => {
+                if let Some(statements) = node.finally_block.clone() {
+                    task.frames.push(Frame::TRY {
+                        node,
+                        stage: TryStage::FINALLY(None),
+                        context: context.clone(),
+                    });
+                    task.push_block(statements, &context);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn run_task_statement(
+        &mut self,
+        statement: &TaskStatement,
+        context: &Context,
+        future: &SharedFuture,
+        task: &mut Task,
+    ) -> Result<(), Interrupt> {
+        match statement {
+            TaskStatement::PLAIN(statement) => self.execute_statement(statement, context),
+            TaskStatement::AWAIT {
+                identifier,
+                expression,
+            } => {
+                let value = match self.solve_expression(expression, context)? {
+                    Value::FUTURE(awaited) if Rc::ptr_eq(&awaited, future) => {
+                        return Err(
+                            RuntimeError::type_error("A task cannot await its own future").into()
+                        );
+                    }
+                    Value::FUTURE(awaited) if !awaited.borrow().is_settled() => {
+                        awaited.borrow_mut().waiters.push(future.clone());
+                        self.event_loop.suspended += 1;
+                        task.waiting = Some((awaited, identifier.clone(), context.clone()));
+                        return Ok(());
+                    }
+                    Value::FUTURE(awaited) => settled_value(&awaited)?,
+                    value => value,
+                };
+                Ok(self.declare(context, identifier, value, None, false)?)
+            }
+            TaskStatement::BLOCK(statements) => {
+                task.push_block(statements.clone(), context);
+                Ok(())
+            }
+            TaskStatement::IF {
+                condition,
+                block,
+                else_block,
+            } => {
+                let block = match self.solve_condition(condition, context)? {
+                    true => Some(block),
+                    false => else_block.as_ref(),
+                };
+                if let Some(block) = block {
+                    task.push_block(block.clone(), context);
+                }
+                Ok(())
+            }
+            TaskStatement::WHILE(node) => {
+                task.frames.push(Frame::WHILE {
+                    node: node.clone(),
+                    context: context.clone(),
+                });
+                Ok(())
+            }
+            TaskStatement::FORIN(node) => {
+                let iterable = self.solve_expression(&node.iterable, context)?;
+                task.frames.push(Frame::FORIN {
+                    node: node.clone(),
+                    elements: iteration_elements(&iterable)?,
+                    position: 0,
+                    context: context.clone(),
+                });
+                Ok(())
+            }
+            TaskStatement::TRY(node) => {
+                task.frames.push(Frame::TRY {
+                    node: node.clone(),
+                    stage: TryStage::TRY,
+                    context: context.clone(),
+                });
+                task.push_block(node.block.clone(), context);
+                Ok(())
+            }
+            TaskStatement::MATCH(node) => {
+                let match_expression = &node.match_expression;
+                let value = self.solve_expression(&match_expression.expression, context)?;
+                let (position, arm_context) =
+                    self.select_match_arm(match_expression, &value, context)?;
+                task.frames.push(Frame::BLOCK {
+                    statements: node.arm_blocks[position].clone(),
+                    position: 0,
+                    context: arm_context,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Pops the frames of a task up to the `try` that handles the interrupt, and returns
+    /// `None` once one does. Otherwise the task is done, it returned or failed.
+    fn unwind_task(
+        &mut self,
+        task: &mut Task,
+        mut interrupt: Interrupt,
+    ) -> Option<Result<Value, RuntimeError>> {
+        while let Some(frame) = task.frames.pop() {
+            let (node, stage, context) = match frame {
+                Frame::TRY {
+                    node,
+                    stage,
+                    context,
+                } => (node, stage, context),
+                _ => continue,
+            };
+
+            let pending = match (stage, interrupt) {
+                (TryStage::TRY, Interrupt::ERROR(error)) => {
+                    match self.select_catch_clause(&node.catch_clauses, error, &context) {
+                        Ok((position, catch_context)) => {
+                            let statements = node.catch_blocks[position].clone();
+                            task.frames.push(Frame::TRY {
+                                node,
+                                stage: TryStage::CATCH,
+                                context,
+                            });
+                            task.frames.push(Frame::BLOCK {
+                                statements,
+                                position: 0,
+                                context: catch_context,
+                            });
+                            return None;
+                        }
+                        Err(interrupt) => interrupt,
+                    }
+                }
+                // An interrupt in `finally` replaces the one it ran for
+                (TryStage::FINALLY(_), next) => {
+                    interrupt = next;
+                    continue;
+                }
+                (_, interrupt) => interrupt,
+            };
+
+            match node.finally_block.clone() {
+                Some(statements) => {
+                    task.frames.push(Frame::TRY {
+                        node,
+                        stage: TryStage::FINALLY(Some(pending)),
+                        context: context.clone(),
+                    });
+                    task.push_block(statements, &context);
+                    return None;
+                }
+                None => interrupt = pending,
+            }
+        }
+
+        Some(match interrupt {
+            Interrupt::RETURN(value) => Ok(value),
+            Interrupt::ERROR(error) => Err(error),
+        })
+    }
+
+    /// Does the next step of the event loop: runs the first ready task, or else fires the
+    /// earliest timer. `false` once there is nothing left to do.
+    fn run_next(&mut self) -> bool {
+        if let Some(task) = self.event_loop.ready.pop_front() {
+            self.resume_task(&task);
+            return true;
+        }
+
+        match self.event_loop.timers.pop_first() {
+            Some(((wakes_at, _), future)) => {
+                self.event_loop.clock = self.event_loop.clock.max(wakes_at);
+                self.settle(&future, FutureState::RESOLVED(Value::NULL));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Settles a future, then makes the tasks awaiting it ready again and settles the
+    /// `Future::all` and `Future::race` futures it completes.
+    fn settle(&mut self, future: &SharedFuture, state: FutureState) {
+        future.borrow_mut().state = state;
+        let mut settled = VecDeque::from([future.clone()]);
+        while let Some(future) = settled.pop_front() {
+            let waiters = std::mem::take(&mut future.borrow_mut().waiters);
+            for waiter in waiters {
+                if waiter.borrow().join.is_none() {
+                    self.event_loop.suspended -= 1;
+                    self.event_loop.ready.push_back(waiter);
+                    continue;
+                }
+                let outcome =
+                    FutureModule::joined_settled(&mut waiter.borrow_mut(), &future.borrow().state);
+                if let Some(state) = outcome {
+                    if let FutureState::REJECTED(_) = state {
+                        self.event_loop.rejected.push(waiter.clone());
+                    }
+                    waiter.borrow_mut().state = state;
+                    settled.push_back(waiter);
+                }
+            }
+        }
+    }
+
+    /// `await value` at the top level of a file: the event loop runs right here until the
+    /// future settles. Async functions are lowered instead, see `tasks::TaskBlock`. Other
+    /// values are returned as they are.
+    pub fn await_value(&mut self, value: Value) -> Result<Value, Interrupt> {
+        let future = match value {
+            Value::FUTURE(future) => future,
+            value => return Ok(value),
+        };
+
+        while !future.borrow().is_settled() {
+            if !self.run_next() {
+                return Err(RuntimeError::stalled_tasks().into());
+            }
+        }
+        settled_value(&future)
+    }
+
+    /// Runs the tasks that are left once the script is done, until every one of them
+    /// finished. A failure nobody awaited is reported like an uncaught error.
+    pub fn run_event_loop(&mut self) -> Result<(), Interrupt> {
+        while self.run_next() {}
+
+        for future in &self.event_loop.rejected {
+            let future = future.borrow();
+            if let (FutureState::REJECTED(error), false) = (&future.state, future.is_awaited) {
+                return Err(Interrupt::ERROR(error.clone()));
+            }
+        }
+        if self.event_loop.suspended > 0 {
+            return Err(RuntimeError::stalled_tasks().into());
+        }
+
+        Ok(())
+    }
+
+    fn solve_member_access(
+        &mut self,
+        member_access: &MemberAccess,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        // `::` names an enum or a module, `.` reaches into a value
+        let parent = match member_access.object.as_identifier() {
+            Some(parent) if self.is_static_name(parent, context) => parent,
+            Some(parent) if member_access.is_static && context.borrow().lookup(parent).is_none() => {
+                return Err(self.native_module_not_imported(parent).into());
+            }
+            _ => {
+                let object = self.solve_expression(&member_access.object, context)?;
+                if member_access.is_static {
+                    return Err(RuntimeError::instance_access(
+                        &object.type_name(),
+                        &member_access.identifier,
+                    )
+                    .into());
+                }
+                return self.solve_value_member(&object, member_access, context);
+            }
+        };
+
+        if !member_access.is_static {
+            return Err(RuntimeError::static_access(parent, &member_access.identifier).into());
+        }
+
+        let enum_declaration = context.borrow().lookup_enum(parent);
+        if let Some(enum_declaration) = enum_declaration {
+            return self.construct_variant(&enum_declaration, member_access, context);
+        }
+
+        let module = context.borrow().lookup_module(parent);
+        if let Some(module) = module {
+            return self.solve_module_member(parent, &module, member_access, context);
+        }
+
+        let native_module = match self.lookup_native_module(parent, context) {
+            Some(native_module) => native_module,
+            None => return Err(self.native_module_not_imported(parent).into()),
+        };
+        match member_access.kind {
+            MemberAccessKind::PROPERTY => Ok(NativeRegistry::get_property(
+                native_module.as_ref(),
+                parent,
+                &member_access.identifier,
+            )?),
+            MemberAccessKind::METHOD => {
+                let arguments = self.solve_arguments(&member_access.args_list, context)?;
+                let result = NativeRegistry::call(
+                    self,
+                    native_module.as_ref(),
+                    parent,
+                    &member_access.identifier,
+                    arguments,
+                );
+                with_frame(result, || static_frame(parent, member_access))
+            }
+        }
+    }
+
+    /// Native modules are reached through the name they were imported as in the file the
+    /// code is written in, or their own name for the prelude.
+    fn lookup_native_module(
+        &self,
+        local_name: &str,
+        context: &Context,
+    ) -> Option<Rc<dyn NativeModule>> {
+        let full_path = context.borrow().lookup_native_module(local_name);
+        match full_path {
+            Some(full_path) => self.natives.find(&full_path),
+            None => self
+                .natives
+                .find_by_name(local_name)
+                .filter(|native_module| native_module.is_prelude()),
+        }
+    }
+
+    fn native_module_not_imported(&self, name: &str) -> RuntimeError {
+        match self.natives.find_by_name(name) {
+            Some(native_module) => RuntimeError {
+                message: format!("Cannot access member of undefined: `{name}`"),
+                ..RuntimeError::undefined(name)
+            }
+            .with_suggestion(&format!(
+                "Consider importing this module with `import {};`",
+                native_module.path()
+            )),
+            None => RuntimeError::undefined(name),
+        }
+    }
+
+    /// Enums and modules in scope are the only names with static members, and a variable
+    /// of the same name shadows them.
+    fn is_static_name(&self, identifier: &str, context: &Context) -> bool {
+        if context.borrow().lookup(identifier).is_some() {
+            return false;
+        }
+
+        context.borrow().lookup_enum(identifier).is_some()
+            || context.borrow().lookup_module(identifier).is_some()
+            || self.lookup_native_module(identifier, context).is_some()
+    }
+
+    fn solve_module_member(
+        &mut self,
+        parent: &str,
+        module: &Module,
+        member_access: &MemberAccess,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        let identifier = &member_access.identifier;
+        let variable = match module.get_export(identifier)? {
+            Export::VALUE(variable) => variable,
+            _ => {
+                return Err(RuntimeError::type_error(&format!(
+                    "`{parent}::{identifier}` is not a value"
+                ))
+                .with_suggestion(&format!(
+                    "Import it with `import {}.{{{identifier}}};`",
+                    module.name
+                ))
+                .into())
+            }
+        };
+
+        match member_access.kind {
+            MemberAccessKind::PROPERTY => Ok(variable.value),
+            MemberAccessKind::METHOD => {
+                let arguments = self.solve_call_arguments(&member_access.args_list, context)?;
+                let result = self.call_with_arguments(&variable.value, arguments);
+                with_frame(result, || static_frame(parent, member_access))
+            }
+        }
+    }
+
+    fn construct_variant(
+        &mut self,
+        enum_declaration: &EnumDeclaration,
+        member_access: &MemberAccess,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        let variant = find_variant(enum_declaration, &member_access.identifier)?;
+        let arguments = match member_access.kind {
+            MemberAccessKind::METHOD => self.solve_arguments(&member_access.args_list, context)?,
+            MemberAccessKind::PROPERTY => Vec::new(),
+        };
+        let name = format!("{}::{}", enum_declaration.identifier, variant.identifier);
+
+        if arguments.len() != variant.payload_types.len() {
+            return Err(RuntimeError::type_error(&format!(
+                "`{name}` expects {} payload value(s), found {}",
+                variant.payload_types.len(),
+                arguments.len()
+            ))
+            .into());
+        }
+
+        let mut payload = Vec::with_capacity(arguments.len());
+        for (argument, payload_type) in arguments.into_iter().zip(&variant.payload_types) {
+            if !type_matches(&argument, payload_type) {
+                return Err(RuntimeError::type_error(&format!(
+                    "`{name}` expects a payload of type `{payload_type}`, found `{}`",
+                    argument.type_name()
+                ))
+                .into());
+            }
+            payload.push(bind_type(argument, payload_type));
+        }
+
+        Ok(Value::ENUM(Rc::new(EnumValue {
+            enum_identifier: enum_declaration.identifier.clone(),
+            variant: variant.identifier.clone(),
+            payload,
+        })))
+    }
+
+    fn solve_match_expression(
+        &mut self,
+        match_expression: &MatchExpression,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        let value = self.solve_expression(&match_expression.expression, context)?;
+        let (position, arm_context) = self.select_match_arm(match_expression, &value, context)?;
+
+        let arm = &match_expression.arms[position];
+        match (&arm.expression, &arm.block) {
+            (Some(expression), _) => self.solve_expression(expression, &arm_context),
+            (None, Some(block)) => self
+                .execute_statements(&block.statements, &arm_context)
+                .map(|_| Value::NULL),
+            (None, None) => Ok(Value::NULL),
+        }
+    }
+
+    /// The first arm whose pattern and guard accept `value`, with the scope holding the
+    /// bindings of its pattern.
+    fn select_match_arm(
+        &mut self,
+        match_expression: &MatchExpression,
+        value: &Value,
+        context: &Context,
+    ) -> Result<(usize, Context), Interrupt> {
+        self.check_exhaustive(match_expression, context)?;
+
+        for (position, arm) in match_expression.arms.iter().enumerate() {
+            let mut bindings = Vec::new();
+            if !self.match_pattern(&arm.pattern, value, &mut bindings, context)? {
+                continue;
+            }
+
+            let arm_context = ExecutionContext::new_child(context, "match", ScopeType::BLOCK);
+            for (identifier, value) in bindings {
+                self.declare(&arm_context, &identifier, value, None, false)?;
+            }
+
+            if let Some(guard) = &arm.guard {
+                if !self.solve_condition(guard, &arm_context)? {
+                    continue;
+                }
+            }
+
+            return Ok((position, arm_context));
+        }
+
+        Err(RuntimeError::type_error(&format!(
+            "No `match` arm matches the value {}",
+            value.repr()
+        ))
+        .into())
+    }
+
+    /// Whether `value` fits `pattern`, collecting the payload bindings on success.
+    fn match_pattern(
+        &mut self,
+        pattern: &Pattern,
+        value: &Value,
+        bindings: &mut Vec<(String, Value)>,
+        context: &Context,
+    ) -> Result<bool, Interrupt> {
+        match pattern.pattern_kind {
+            PatternKind::WILDCARD => Ok(true),
+            PatternKind::BINDING => {
+                bindings.push((pattern.identifier.clone(), value.clone()));
+                Ok(true)
+            }
+            PatternKind::LITERAL => {
+                let literal = self.solve_literal(pattern.literal.as_ref().unwrap(), context)?;
+                Ok(values_equal(&literal, value))
+            }
+            PatternKind::VARIANT => {
+                let enum_value = match value {
+                    Value::ENUM(enum_value)
+                        if enum_value.enum_identifier == pattern.enum_identifier
+                            && enum_value.variant == pattern.identifier =>
+                    {
+                        enum_value.clone()
+                    }
+                    _ => return Ok(false),
+                };
+
+                for (sub_pattern, payload) in pattern.payload.iter().zip(&enum_value.payload) {
+                    if !self.match_pattern(sub_pattern, payload, bindings, context)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    /// A `match` is exhaustive when an unguarded arm accepts any value, when unguarded
+    /// arms cover both `bool` values, or when they cover every variant of the matched
+    /// enum with irrefutable payload patterns.
+    fn check_exhaustive(
+        &self,
+        match_expression: &MatchExpression,
+        context: &Context,
+    ) -> Result<(), RuntimeError> {
+        let mut enum_declaration: Option<Rc<EnumDeclaration>> = None;
+
+        for arm in &match_expression.arms {
+            let pattern = &arm.pattern;
+            if !matches!(pattern.pattern_kind, PatternKind::VARIANT) {
+                continue;
+            }
+
+            let declaration = match context.borrow().lookup_enum(&pattern.enum_identifier) {
+                Some(declaration) => declaration,
+                None => return Err(RuntimeError::undefined(&pattern.enum_identifier)),
+            };
+            let variant = find_variant(&declaration, &pattern.identifier)?;
+            if pattern.payload.len() != variant.payload_types.len() {
+                return Err(RuntimeError::type_error(&format!(
+                    "Pattern `{}::{}` expects {} payload pattern(s), found {}",
+                    declaration.identifier,
+                    variant.identifier,
+                    variant.payload_types.len(),
+                    pattern.payload.len()
+                )));
+            }
+
+            match &enum_declaration {
+                Some(previous) if previous.identifier != declaration.identifier => {
+                    return Err(RuntimeError::type_error(&format!(
+                        "A `match` cannot mix variants of `{}` and `{}`",
+                        previous.identifier, declaration.identifier
+                    )));
+                }
+                Some(_) => {}
+                None => enum_declaration = Some(declaration),
+            }
+        }
+
+        let unguarded: Vec<&Pattern> = match_expression
+            .arms
+            .iter()
+            .filter(|arm| arm.guard.is_none())
+            .map(|arm| &arm.pattern)
+            .collect();
+
+        if unguarded.iter().any(|pattern| is_irrefutable(pattern)) {
+            return Ok(());
+        }
+
+        let missing: Vec<String> = match &enum_declaration {
+            Some(declaration) => declaration
+                .variants
+                .iter()
+                .filter(|variant| {
+                    !unguarded.iter().any(|pattern| {
+                        matches!(pattern.pattern_kind, PatternKind::VARIANT)
+                            && pattern.identifier == variant.identifier
+                            && pattern.payload.iter().all(is_irrefutable)
+                    })
+                })
+                .map(|variant| format!("{}::{}", declaration.identifier, variant.identifier))
+                .collect(),
+            None if match_expression
+                .arms
+                .iter()
+                .any(|arm| boolean_pattern(&arm.pattern).is_some()) =>
+            {
+                ["true", "false"]
+                    .into_iter()
+                    .filter(|boolean| {
+                        !unguarded
+                            .iter()
+                            .any(|pattern| boolean_pattern(pattern) == Some(boolean))
+                    })
+                    .map(String::from)
+                    .collect()
+            }
+            // Only `bool` and enums can be covered without a catch-all arm
+            None => {
+                return Err(RuntimeError::type_error(
+                    "Non-exhaustive `match`: add a `_` arm to cover the remaining values",
+                ))
+            }
+        };
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let missing: Vec<String> = missing.iter().map(|pattern| format!("`{pattern}`")).collect();
+        Err(RuntimeError::type_error(&format!(
+            "Non-exhaustive `match`: {} not covered",
+            missing.join(", ")
+        )))
+    }
+
+    fn solve_value_member(
+        &mut self,
+        value: &Value,
+        member_access: &MemberAccess,
+        context: &Context,
+    ) -> Result<Value, Interrupt> {
+        match (value, &member_access.kind) {
+            (Value::ARRAY(array), MemberAccessKind::PROPERTY) => {
+                Ok(ArrayModule::get_property(array, &member_access.identifier)?)
+            }
+            (Value::MAP(map), MemberAccessKind::PROPERTY) => {
+                Ok(MapModule::get_property(map, &member_access.identifier)?)
+            }
+            (Value::MAP(map), MemberAccessKind::METHOD) => {
+                let arguments = self.solve_arguments(&member_access.args_list, context)?;
+                let result = MapModule::call_method(map, &member_access.identifier, arguments);
+                with_frame(result.map_err(Interrupt::from), || method_frame(member_access))
+            }
+            (Value::ARRAY(array), MemberAccessKind::METHOD) => {
+                let arguments = self.solve_arguments(&member_access.args_list, context)?;
+                let result =
+                    ArrayModule::call_method(self, array, &member_access.identifier, arguments);
+                with_frame(result, || method_frame(member_access))
+            }
+            (Value::ERROR(error), MemberAccessKind::PROPERTY) => {
+                Ok(ErrorModule::get_property(error, &member_access.identifier)?)
+            }
+            (Value::INT(_) | Value::FLOAT(_), MemberAccessKind::METHOD) => {
+                let arguments = self.solve_arguments(&member_access.args_list, context)?;
+                let result = NumberModule::call_method(value, &member_access.identifier, arguments);
+                with_frame(result.map_err(Interrupt::from), || method_frame(member_access))
+            }
+            (Value::STRING(string), MemberAccessKind::PROPERTY) => {
+                Ok(StringModule::get_property(string, &member_access.identifier)?)
+            }
+            (Value::STRING(string), MemberAccessKind::METHOD) => {
+                let arguments = self.solve_arguments(&member_access.args_list, context)?;
+                let result = StringModule::call_method(string, &member_access.identifier, arguments);
+                with_frame(result.map_err(Interrupt::from), || method_frame(member_access))
+            }
+            _ => Err(RuntimeError::type_error(&format!(
+                "`{}` has no member `{}`",
+                value.type_name(),
+                member_access.identifier
+            ))
+            .into()),
+        }
+    }
+}
+
+/// Adds the frame of a call to an error unwinding out of it.
+/// A native module is imported into the scope of the file, so that functions reach it
+/// through their closure wherever they are called from.
+fn import_native_module(imported_module: ImportedModule, context: &Context) {
+    let local_name = imported_module.local_name().to_string();
+    context
+        .borrow_mut()
+        .scoped_native_modules
+        .entry(local_name)
+        .or_insert(imported_module.full_path);
+}
+
+fn with_frame(
+    result: Result<Value, Interrupt>,
+    frame: impl FnOnce() -> String,
+) -> Result<Value, Interrupt> {
+    result.map_err(|interrupt| match interrupt {
+        Interrupt::ERROR(mut error) => {
+            error.stack.push(frame());
+            Interrupt::ERROR(error)
+        }
+        interrupt => interrupt,
+    })
+}
+
+/// `at math::sqrt() called on line 3`
+fn static_frame(parent: &str, member_access: &MemberAccess) -> String {
+    format!(
+        "at {parent}::{}() called on line {}",
+        member_access.identifier, member_access.line
+    )
+}
+
+/// `at .map() called on line 3`
+fn method_frame(member_access: &MemberAccess) -> String {
+    format!(
+        "at .{}() called on line {}",
+        member_access.identifier, member_access.line
+    )
+}
+
+/// Places each argument in the slot of its parameter. Slots left empty have a default value.
+fn bind_arguments(
+    declaration: &FunctionDeclaration,
+    arguments: Vec<(Option<String>, Value)>,
+) -> Result<Vec<Option<Value>>, RuntimeError> {
+    let parameters = &declaration.parameter_list;
+    let name = &declaration.identifier;
+    let mut slots: Vec<Option<Value>> = vec![None; parameters.len()];
+    let positional = arguments.iter().filter(|(identifier, _)| identifier.is_none()).count();
+
+    if positional > parameters.len() {
+        return Err(RuntimeError::type_error(&format!(
+            "`{name}` expects at most {} argument(s), found {positional}",
+            parameters.len()
+        )));
+    }
+
+    let mut is_named = false;
+    for (position, (identifier, value)) in arguments.into_iter().enumerate() {
+        let index = match identifier {
+            None if is_named => {
+                return Err(RuntimeError::type_error(&format!(
+                    "Positional arguments cannot follow named arguments in a call to `{name}`"
+                )))
+            }
+            None => position,
+            Some(identifier) => {
+                is_named = true;
+                parameters
+                    .iter()
+                    .position(|parameter| parameter.identifier == identifier)
+                    .ok_or_else(|| {
+                        RuntimeError::type_error(&format!(
+                            "`{name}` has no parameter named `{identifier}`"
+                        ))
+                    })?
+            }
+        };
+
+        if slots[index].is_some() {
+            return Err(RuntimeError::type_error(&format!(
+                "`{name}` received more than one value for `{}`",
+                parameters[index].identifier
+            )));
+        }
+        slots[index] = Some(value);
+    }
+
+    for (parameter, slot) in parameters.iter().zip(&slots) {
+        if slot.is_none() && parameter.default_value.is_none() {
+            return Err(RuntimeError::type_error(&format!(
+                "`{name}` is missing an argument for `{}`",
+                parameter.identifier
+            )));
+        }
+    }
+
+    Ok(slots)
+}
+
+
+fn find_variant<'a>(
+    enum_declaration: &'a EnumDeclaration,
+    identifier: &str,
+) -> Result<&'a EnumVariant, RuntimeError> {
+    enum_declaration
+        .variants
+        .iter()
+        .find(|variant| variant.identifier == identifier)
+        .ok_or_else(|| RuntimeError {
+            message: format!(
+                "`{}` has no variant `{identifier}`",
+                enum_declaration.identifier
+            ),
+            ..RuntimeError::undefined(identifier)
+        })
+}
+
+fn is_irrefutable(pattern: &Pattern) -> bool {
+    matches!(pattern.pattern_kind, PatternKind::WILDCARD | PatternKind::BINDING)
+}
+
+fn boolean_pattern(pattern: &Pattern) -> Option<&str> {
+    match &pattern.literal {
+        Some(literal) if matches!(literal.literal_kind, LiteralKind::BOOLEAN) => {
+            Some(literal.value.as_str())
+        }
+        _ => None,
+    }
+}
+
+/// The value of a settled future, or its failure. Either way, whoever asks for it handles
+/// a failure of the future.
+fn settled_value(future: &SharedFuture) -> Result<Value, Interrupt> {
+    let mut future = future.borrow_mut();
+    future.mark_awaited();
+    match &future.state {
+        FutureState::RESOLVED(value) => Ok(value.clone()),
+        FutureState::REJECTED(error) => Err(Interrupt::ERROR(error.clone())),
+        _ => Ok(Value::NULL),
+    }
+}
+
+/// Checks the value a function returned against its return type. The body of
+/// `async function f() -> Future<T>` returns a `T`.
+fn check_return_value(
+    declaration: &FunctionDeclaration,
+    value: Value,
+) -> Result<Value, RuntimeError> {
+    let return_type = match declaration.return_type.type_parameters.first() {
+        Some(inner) if declaration.is_async && declaration.return_type.type_identifier == "Future" => inner,
+        _ => &declaration.return_type,
+    };
+
+    if return_type.type_identifier.is_empty() {
+        return Ok(value);
+    }
+
+    if !type_matches(&value, return_type) {
+        return Err(RuntimeError::type_error(&format!(
+            "`{}` should return `{return_type}`, but returned `{}`",
+            declaration.identifier,
+            value.type_name()
+        )));
+    }
+
+    Ok(bind_type(value, return_type))
+}
+
+/// The values `for (x in iterable)` goes through: the elements of an array or the keys of
+/// a map, as they were when the loop started.
+fn iteration_elements(iterable: &Value) -> Result<Vec<Value>, RuntimeError> {
+    match iterable {
+        Value::ARRAY(array) => Ok(array.borrow().elements.clone()),
+        Value::MAP(map) => Ok(map.borrow().entries.iter().map(|(key, _)| key.clone()).collect()),
+        _ => Err(RuntimeError::type_error(&format!(
+            "Cannot iterate over a value of type `{}`",
+            iterable.type_name()
+        ))),
+    }
+}
+
+fn check_type(value: &Value, type_def: &Type, identifier: &str) -> Result<(), RuntimeError> {
+    if type_matches(value, type_def) {
+        return Ok(());
+    }
+
+    Err(RuntimeError::type_error(&format!(
+        "Cannot assign a value of type `{}` to `{identifier}` of type `{type_def}`",
+        value.type_name()
+    )))
+}
+
+pub fn set_index_value(target: &Value, index: Value, value: Value) -> Result<(), RuntimeError> {
+    match target {
+        Value::ARRAY(array) => ArrayModule::set_index(array, &index, value),
+        Value::MAP(map) => MapModule::set_index(map, index, value),
+        _ => Err(RuntimeError::type_error(&format!(
+            "Cannot index into a value of type `{}`",
+            target.type_name()
+        ))),
+    }
+}
+
+pub fn index_value(value: &Value, index: &Value) -> Result<Value, RuntimeError> {
+    match value {
+        Value::ARRAY(array) => ArrayModule::get_index(array, index),
+        Value::MAP(map) => MapModule::get_index(map, index),
+        _ => Err(RuntimeError::type_error(&format!(
+            "Cannot index into a value of type `{}`",
+            value.type_name()
+        ))),
+    }
+}
+
+pub fn apply_binary_operator(
+    operator: &str,
+    first: &Value,
+    second: &Value,
+) -> Result<Value, RuntimeError> {
+    match (operator, first, second) {
+        ("==", _, _) => Ok(Value::BOOL(values_equal(first, second))),
+        ("!=", _, _) => Ok(Value::BOOL(!values_equal(first, second))),
+        ("&&", Value::BOOL(a), Value::BOOL(b)) => Ok(Value::BOOL(*a && *b)),
+        ("||", Value::BOOL(a), Value::BOOL(b)) => Ok(Value::BOOL(*a || *b)),
+        ("+", Value::STRING(a), Value::STRING(b)) => Ok(Value::STRING(format!("{a}{b}"))),
+        ("+" | "-" | "*" | "/" | "%", _, _) if is_numeric(first) && is_numeric(second) => {
+            apply_arithmetic(operator, first, second)
+        }
+        ("<" | ">" | "<=" | ">=", _, _) => {
+            let ordering = compare_values(first, second)?;
+            Ok(Value::BOOL(match operator {
+                "<" => ordering.is_lt(),
+                ">" => ordering.is_gt(),
+                "<=" => ordering.is_le(),
+                _ => ordering.is_ge(),
+            }))
+        }
+        _ => Err(RuntimeError::type_error(&format!(
+            "Operator `{operator}` cannot be applied to `{}` and `{}`",
+            first.type_name(),
+            second.type_name()
+        ))),
+    }
+}
@@ -1,176 +1,178 @@
-#![allow(dead_code)]
-use handlers::{
-    handle_args_list, handle_binary_expression, handle_binary_operator, handle_boolean_literal,
-    handle_declaration, handle_expression, handle_function_call, handle_identifier,
-    handle_import_stmt, handle_literal, handle_member_access, handle_number_literal,
-    handle_string_literal, handle_unary_expression, handle_var_declaration,
-};
-use pest::Parser;
+use handlers::{handle_declaration, handle_import_stmt, handle_statement};
+use interpreter::Interpreter;
+use colored::Colorize;
+use pest::{iterators::Pair, Parser};
 use pest_derive::Parser;
-use std::fs;
-use structure::{DeclarationKind, ExecutionContext, ExpressionKind, ImportedModules};
+use std::{env, fs, path::Path, thread};
 
 mod errors;
 mod handlers;
+mod interpreter;
+mod modules;
+mod numeric;
+mod runtime;
 mod standard_library;
 mod structure;
+mod tasks;
+mod validation;
 
 use crate::{
-    errors::{throw_undefined, throw_undefined_class_error},
-    structure::ArgumentKind,
-    standard_library::console::ConsoleModule
+    errors::{throw_runtime_error, ProgramError, RuntimeError},
+    runtime::Interrupt,
+    validation::validate_program,
 };
 
 #[derive(Parser)]
 #[grammar = "src/grammar.pest"]
 struct WSParser;
 
-fn read_file(file_path: &str) -> String {
-    return fs::read_to_string(file_path).ok().unwrap();
+fn read_file(file_path: &str) -> Result<String, RuntimeError> {
+    fs::read_to_string(file_path)
+        .map_err(|error| RuntimeError::io_error(&format!("Cannot read `{file_path}`: {error}")))
+}
+
+/// Classes, interfaces and enums only describe types, so they can be declared before
+/// anything else runs, imports included.
+fn is_type_declaration(pair: &Pair<'_, Rule>) -> bool {
+    pair.as_rule() == Rule::declaration
+        && matches!(
+            pair.clone().into_inner().next().unwrap().as_rule(),
+            Rule::class_declaration | Rule::interface_declaration | Rule::enum_declaration
+        )
+}
+
+fn is_hoisted(pair: &Pair<'_, Rule>) -> bool {
+    match pair.as_rule() {
+        Rule::import_stmt => true,
+        Rule::declaration => {
+            pair.clone().into_inner().next().unwrap().as_rule() == Rule::function_declaration
+        }
+        _ => false,
+    }
 }
 
+fn execute_top_level(interpreter: &mut Interpreter, pair: Pair<'_, Rule>) -> Result<(), Interrupt> {
+    let global_context = interpreter.global_context.clone();
+
+    match pair.as_rule() {
+        Rule::import_stmt => {
+            let module = handle_import_stmt(pair);
+            interpreter.execute_import(module, &global_context)
+        }
+        Rule::declaration => {
+            let declaration = handle_declaration(pair);
+            interpreter.execute_declaration(&declaration, &global_context)
+        }
+        Rule::stmt => {
+            let statement = handle_statement(pair);
+            interpreter.execute_statement(&statement, &global_context)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Runs every top level item of a file.
+pub fn execute_program(
+    interpreter: &mut Interpreter,
+    program_pair: Pair<'_, Rule>,
+) -> Result<(), Box<ProgramError>> {
+    validate_program(&program_pair)?;
+
+    // Type declarations come first, then imports and function declarations are hoisted,
+    // every other statement then runs in source order
+    let (types, others): (Vec<_>, Vec<_>) =
+        program_pair.into_inner().partition(is_type_declaration);
+    let (hoisted, statements): (Vec<_>, Vec<_>) = others.into_iter().partition(is_hoisted);
+
+    for inner_pair in types.into_iter().chain(hoisted).chain(statements) {
+        let span_str = inner_pair.as_str().trim().to_string();
+        let (line, _) = inner_pair.as_span().start_pos().line_col();
+
+        let error = match execute_top_level(interpreter, inner_pair) {
+            Ok(()) => continue,
+            Err(Interrupt::ERROR(error)) => error,
+            Err(Interrupt::RETURN(_)) => {
+                RuntimeError::type_error("`return` can only be used inside a function")
+            }
+        };
+
+        return Err(Box::new(ProgramError { error, span_str, line }));
+    }
+
+    Ok(())
+}
+
+/// Every call of a program nests several Rust frames, the main thread stack is too
+/// small for `interpreter::MAX_CALL_DEPTH` calls
+const INTERPRETER_STACK_SIZE: usize = 256 * 1024 * 1024;
+
 fn main() {
-    let input_code = read_file("tests/1.ws");
+    let interpreter_thread = thread::Builder::new()
+        .name(String::from("interpreter"))
+        .stack_size(INTERPRETER_STACK_SIZE)
+        .spawn(run)
+        .expect("cannot start the interpreter thread");
+    if interpreter_thread.join().is_err() {
+        std::process::exit(101);
+    }
+}
+
+/// `interpreter2 [--allow-process] [program.ws] [arguments...]`
+fn run() {
+    let mut arguments = env::args().skip(1).peekable();
+    let mut allow_process = false;
+    while let Some(option) = arguments.next_if(|argument| argument.starts_with("--")) {
+        match option.as_str() {
+            "--allow-process" => allow_process = true,
+            _ => {
+                println!("{}: Unknown option `{option}`", "Error".red().bold());
+                std::process::exit(1);
+            }
+        }
+    }
+    let file_path = arguments.next().unwrap_or(String::from("tests/1.ws"));
+    let program_arguments: Vec<String> = arguments.collect();
+    let input_code = match read_file(&file_path) {
+        Ok(input_code) => input_code,
+        Err(error) => {
+            println!("{}: {}", error.kind_name().red().bold(), error.message);
+            std::process::exit(1);
+        }
+    };
 
     match WSParser::parse(Rule::program, &input_code) {
         Ok(program_pairs) => {
             for program_pair in program_pairs {
-                let mut imported_modules = ImportedModules::default();
-                let mut global_execution_context = ExecutionContext::default();
-                let mut line: usize = 1;
-
-                // println!("{program_pair:#?}");
-
-                for inner_pair in program_pair.into_inner() {
-                    match inner_pair.as_rule() {
-                        Rule::program => {}
-                        Rule::import_stmt => {
-                            let module = handle_import_stmt(inner_pair);
-                            imported_modules.modules.push(module);
-                        }
-                        Rule::module_name => {}
-                        Rule::declaration => {
-                            let declaration = handle_declaration(inner_pair);
-
-                            match declaration.declaration_kind {
-                                DeclarationKind::VAR => {
-                                    global_execution_context
-                                        .scoped_variables
-                                        .push(declaration.var_declaration.unwrap());
-                                }
-                                DeclarationKind::CONST => todo!(),
-                                DeclarationKind::CLASS => todo!(),
-                                DeclarationKind::INTERFACE => todo!(),
-                                DeclarationKind::FUNCTION => todo!(),
-                            }
-                        }
-                        Rule::definition => {}
-                        Rule::var_declaration => global_execution_context
-                            .scoped_variables
-                            .push(handle_var_declaration(inner_pair)),
-                        Rule::const_declaration => {}
-                        Rule::class_declaration => {}
-                        Rule::interface_declaration => {}
-                        Rule::function_declaration => {}
-                        Rule::parameter_list => {}
-                        Rule::parameter => {}
-                        Rule::property_declaration => {}
-                        Rule::method_declaration => {}
-                        Rule::interface_method_declaration => {}
-                        Rule::access_modifier => {}
-                        Rule::member => {}
-                        Rule::stmt => {}
-                        Rule::expression => {
-                            let expression = handle_expression(inner_pair);
-                        }
-                        Rule::unary_expression => {
-                            let unary_expression = handle_unary_expression(inner_pair);
-                        }
-                        Rule::binary_expression => {
-                            let binary_expression = handle_binary_expression(inner_pair);
-                        }
-                        Rule::prefix_expression => {}
-                        Rule::postfix_expression => {}
-                        Rule::literal => {
-                            let literal = handle_literal(inner_pair);
-                        }
-                        Rule::number_literal => {
-                            let literal = handle_number_literal(inner_pair);
-                        }
-                        Rule::string_literal => {
-                            let literal = handle_string_literal(inner_pair);
-                        }
-                        Rule::boolean_literal => {
-                            let literal = handle_boolean_literal(inner_pair);
-                        }
-                        Rule::if_stmt => {}
-                        Rule::while_stmt => {}
-                        Rule::for_stmt => {}
-                        Rule::function_call => {
-                            let function = handle_function_call(inner_pair);
-                        }
-                        Rule::method_call => {}
-                        Rule::arg_list => {
-                            let args_list = handle_args_list(inner_pair);
-                        }
-                        Rule::member_access_infix => {}
-                        Rule::member_access => {
-                            let span_str = inner_pair.as_str();
-                            let member = handle_member_access(inner_pair);
-
-                            match member.parent.as_str() {
-                                "Console" => {
-                                    let found_module = imported_modules
-                                        .modules
-                                        .iter()
-                                        .find(|module| module.name == "Console");
-
-                                    match found_module {
-                                        Some(t) => {}
-                                        None => {
-                                            println!("{:}", throw_undefined_class_error(
-                                                "Console",
-                                                span_str,
-                                                0,
-                                                line,
-                                                "Consider importing this module with `import std.Console;`",
-                                            )
-                                            .as_str());
-                                            std::process::exit(1);
-                                        }
-                                    }
-
-                                    match member.identifier.as_str() {
-                                        "print" => ConsoleModule::print(member.args_list, &global_execution_context),
-                                        "println" => ConsoleModule::println(member.args_list, &global_execution_context),
-                                        _ => {}
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-                        Rule::r#type => {}
-                        Rule::type_array => {}
-                        Rule::simple_type => {}
-                        Rule::void => {}
-                        Rule::generic_type => {}
-                        Rule::type_argument => {}
-                        Rule::identifier => {
-                            let identifier = handle_identifier(inner_pair);
-                        }
-                        Rule::binary_operator => {
-                            let binary_operator = handle_binary_operator(inner_pair);
-                        }
-                        Rule::prefix_operator => {}
-                        Rule::postfix_operator => {}
-                        Rule::WHITESPACE => {}
-                        Rule::ASCII_ALPHANUMERIC => {}
-                        Rule::block => {}
-                        Rule::new_line => {
-                            line += 1;
-                        }
-                        _ => {}
-                    }
+                let mut interpreter = Interpreter {
+                    program_arguments: program_arguments.clone(),
+                    allow_process,
+                    ..Default::default()
+                };
+                let root_dir = Path::new(&file_path)
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default();
+                interpreter.modules.current_dir = root_dir.clone();
+                if let Ok(entry_path) = Path::new(&file_path).canonicalize() {
+                    let entry_name = entry_path.file_stem().unwrap_or_default();
+                    let entry_name = entry_name.to_string_lossy().to_string();
+                    interpreter.modules.loading.push((entry_name, entry_path));
+                }
+                interpreter.modules.search_paths.push(root_dir);
+                if let Some(search_path) = env::var_os("WS_PATH") {
+                    interpreter.modules.search_paths.extend(env::split_paths(&search_path));
+                }
+
+                if let Err(program_error) = execute_program(&mut interpreter, program_pair) {
+                    let ProgramError { error, span_str, line } = *program_error;
+                    println!("{}", throw_runtime_error(&error, &span_str, line));
+                    std::process::exit(1);
+                }
+
+                // Async tasks that were never awaited have no statement to point at
+                if let Err(Interrupt::ERROR(error)) = interpreter.run_event_loop() {
+                    println!("{}", throw_runtime_error(&error, "", 0));
+                    std::process::exit(1);
                 }
             }
         }
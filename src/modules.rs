@@ -0,0 +1,250 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs, mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use pest::{iterators::Pair, Parser};
+
+use crate::{
+    errors::{ProgramError, RuntimeError},
+    execute_program, is_type_declaration,
+    interpreter::Interpreter,
+    runtime::Variable,
+    structure::{
+        ClassDeclaration, EnumDeclaration, ExecutionContext, ImportedModule, InterfaceDeclaration,
+    },
+    Rule, WSParser,
+};
+
+/// A user `.ws` file, evaluated once and shared by every file importing it.
+#[derive(Debug)]
+pub struct Module {
+    pub name: String,
+    pub path: PathBuf,
+    pub context: Rc<RefCell<ExecutionContext>>,
+}
+
+/// A `public` declaration of a module. Variables are exported by value, so importers see
+/// the value they had once the module finished running.
+#[allow(clippy::upper_case_acronyms)]
+pub enum Export {
+    VALUE(Variable),
+    ENUM(Rc<EnumDeclaration>),
+    CLASS(ClassDeclaration),
+    INTERFACE(InterfaceDeclaration),
+}
+
+impl Module {
+    pub fn get_export(&self, identifier: &str) -> Result<Export, RuntimeError> {
+        let context = self.context.borrow();
+
+        let export = if let Some(variable) = context.scoped_variables.get(identifier) {
+            Export::VALUE(variable.clone())
+        } else if let Some(enum_declaration) = context.lookup_enum(identifier) {
+            Export::ENUM(enum_declaration)
+        } else if let Some(class) = context.scoped_classes.iter().find(|c| c.identifier == identifier) {
+            Export::CLASS(class.clone())
+        } else if let Some(interface) =
+            context.scoped_interfaces.iter().find(|i| i.identifier == identifier)
+        {
+            Export::INTERFACE(interface.clone())
+        } else {
+            let mut public_names: Vec<&str> =
+                context.public_names.iter().map(String::as_str).collect();
+            public_names.sort_unstable();
+            let error = RuntimeError {
+                identifier: Some(identifier.to_string()),
+                ..RuntimeError::import_error(&format!(
+                    "Module `{}` has no member `{identifier}`",
+                    self.name
+                ))
+            };
+            return Err(match public_names.is_empty() {
+                true => error,
+                false => error.with_suggestion(&format!(
+                    "`{}` exports {}",
+                    self.name,
+                    public_names.join(", ")
+                )),
+            });
+        };
+
+        if !context.public_names.contains(identifier) {
+            return Err(RuntimeError {
+                identifier: Some(identifier.to_string()),
+                ..RuntimeError::import_error(&format!(
+                    "`{identifier}` is private to module `{}`",
+                    self.name
+                ))
+            }
+            .with_suggestion(&format!(
+                "Mark it `public` in `{}` to export it",
+                self.path.display()
+            )));
+        }
+
+        Ok(export)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModuleResolver {
+    /// Directories searched, in order, when a module is not next to the importing file.
+    /// The directory of the program being run comes first.
+    pub search_paths: Vec<PathBuf>,
+    /// Directory of the file being executed
+    pub current_dir: PathBuf,
+    /// Evaluated modules by canonical path
+    pub cache: HashMap<PathBuf, Rc<Module>>,
+    /// Modules whose imports are running, outermost first, to detect cycles
+    pub loading: Vec<(String, PathBuf)>,
+}
+
+impl ModuleResolver {
+    /// `utils.math` is looked up as `utils/math.ws` next to the importing file, then in
+    /// each search path.
+    pub fn resolve(&self, full_path: &str) -> Result<PathBuf, RuntimeError> {
+        let relative_path = full_path
+            .split('.')
+            .map(str::trim)
+            .collect::<PathBuf>()
+            .with_extension("ws");
+        let mut candidates: Vec<PathBuf> = Vec::new();
+        for directory in std::iter::once(&self.current_dir).chain(&self.search_paths) {
+            let candidate = directory.join(&relative_path);
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+
+        match candidates.iter().find(|candidate| candidate.is_file()) {
+            Some(path) => Ok(path.canonicalize().unwrap_or_else(|_| path.clone())),
+            None => {
+                let searched: Vec<String> = candidates
+                    .iter()
+                    .map(|candidate| format!("`{}`", candidate.display()))
+                    .collect();
+                Err(RuntimeError {
+                    identifier: Some(full_path.to_string()),
+                    ..RuntimeError::import_error(&format!("Cannot find module `{full_path}`"))
+                }
+                .with_suggestion(&format!("Searched {}", searched.join(", "))))
+            }
+        }
+    }
+
+    /// Evaluates the module on its first import. Every module runs in its own global
+    /// scope, with its own imports, and resolves its imports from its own directory.
+    pub fn import(
+        interpreter: &mut Interpreter,
+        imported_module: &ImportedModule,
+    ) -> Result<Rc<Module>, RuntimeError> {
+        let full_path = &imported_module.full_path;
+        let path = match interpreter.modules.resolve(full_path) {
+            Ok(path) => path,
+            Err(error) => {
+                return Err(match interpreter.natives.suggest(full_path) {
+                    Some(native_path) => {
+                        error.with_suggestion(&format!("Did you mean `{native_path}`?"))
+                    }
+                    None => error,
+                })
+            }
+        };
+        if let Some(module) = interpreter.modules.cache.get(&path) {
+            return Ok(module.clone());
+        }
+        interpreter.modules.check_cycle(&imported_module.full_path, &path)?;
+
+        let input_code = fs::read_to_string(&path).map_err(|error| {
+            RuntimeError::io_error(&format!("Cannot read `{}`: {error}", path.display()))
+        })?;
+        let program_pair = Self::parse(&input_code, &path)?;
+        let context = Interpreter::new_global_context();
+        let module = Rc::new(Module {
+            name: imported_module.full_path.clone(),
+            path: path.clone(),
+            context: context.clone(),
+        });
+
+        // Types are declared before imports run, so a module that only declares types is
+        // complete enough to be shared with the modules it imports, cycles included
+        if program_pair.clone().into_inner().all(|pair| {
+            matches!(pair.as_rule(), Rule::import_stmt | Rule::EOI) || is_type_declaration(&pair)
+        }) {
+            interpreter.modules.cache.insert(path.clone(), module.clone());
+        }
+
+        let global_context = mem::replace(&mut interpreter.global_context, context.clone());
+        let current_dir = mem::replace(
+            &mut interpreter.modules.current_dir,
+            path.parent().map_or_else(PathBuf::new, Path::to_path_buf),
+        );
+
+        interpreter
+            .modules
+            .loading
+            .push((imported_module.full_path.clone(), path.clone()));
+
+        let result = Self::evaluate(interpreter, program_pair, &path);
+
+        interpreter.modules.loading.pop();
+        interpreter.global_context = global_context;
+        interpreter.modules.current_dir = current_dir;
+        result?;
+
+        interpreter.modules.cache.insert(path, module.clone());
+        Ok(module)
+    }
+
+    /// Importing a module that is still running its own imports would run it twice.
+    /// Each file along the cycle adds the line of its `import` as the error unwinds.
+    fn check_cycle(&self, full_path: &str, path: &Path) -> Result<(), RuntimeError> {
+        let start = match self.loading.iter().position(|(_, loading)| loading == path) {
+            Some(start) => start,
+            None => return Ok(()),
+        };
+
+        let chain: Vec<String> = self.loading[start..]
+            .iter()
+            .map(|(name, _)| format!("`{name}`"))
+            .chain(std::iter::once(format!("`{full_path}`")))
+            .collect();
+
+        Err(RuntimeError {
+            identifier: Some(full_path.to_string()),
+            ..RuntimeError::import_error(&format!("Circular import: {}", chain.join(" -> ")))
+        }
+        .with_suggestion(
+            "Move the shared declarations into a separate module, or keep only classes, interfaces and enums in one of the modules",
+        ))
+    }
+
+    fn parse<'a>(input_code: &'a str, path: &Path) -> Result<Pair<'a, Rule>, RuntimeError> {
+        match WSParser::parse(Rule::program, input_code) {
+            Ok(mut program_pairs) => Ok(program_pairs.next().unwrap()),
+            Err(error) => Err(RuntimeError::syntax_error(&format!(
+                "Cannot parse `{}`\n{error}",
+                path.display()
+            ))),
+        }
+    }
+
+    fn evaluate(
+        interpreter: &mut Interpreter,
+        program_pair: Pair<'_, Rule>,
+        path: &Path,
+    ) -> Result<(), RuntimeError> {
+        execute_program(interpreter, program_pair).map_err(|program_error| {
+            let ProgramError { mut error, span_str, line } = *program_error;
+            let span_str = span_str.lines().next().unwrap_or_default().trim();
+            error
+                .stack
+                .push(format!("in {}:{line} `{span_str}`", path.display()));
+            error
+        })
+    }
+}
@@ -0,0 +1,155 @@
+// Numeric tower: `int` is a checked 64-bit integer, `float` an IEEE-754 double.
+// Mixing the two in arithmetic or comparisons widens the `int` to `float`; narrowing
+// back to `int` only happens through an explicit `toInt()`.
+use std::cmp::Ordering;
+
+use crate::{errors::RuntimeError, runtime::Value};
+
+pub fn parse_integer_literal(literal: &str) -> Result<i64, RuntimeError> {
+    let cleaned = literal.replace('_', "");
+    let (is_negative, digits) = match cleaned.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, cleaned.as_str()),
+    };
+
+    let magnitude = if let Some(hex) = digits.strip_prefix("0x") {
+        i128::from_str_radix(hex, 16)
+    } else if let Some(binary) = digits.strip_prefix("0b") {
+        i128::from_str_radix(binary, 2)
+    } else {
+        digits.parse::<i128>()
+    };
+
+    let value = match magnitude {
+        Ok(magnitude) if is_negative => -magnitude,
+        Ok(magnitude) => magnitude,
+        Err(_) => {
+            return Err(RuntimeError::syntax_error(&format!(
+                "Invalid integer literal `{literal}`"
+            )))
+        }
+    };
+
+    i64::try_from(value).map_err(|_| {
+        RuntimeError::syntax_error(&format!(
+            "Integer literal `{literal}` does not fit in a 64-bit `int`"
+        ))
+    })
+}
+
+pub fn parse_float_literal(literal: &str) -> Result<f64, RuntimeError> {
+    match literal.replace('_', "").parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        Ok(_) => Err(RuntimeError::syntax_error(&format!(
+            "Float literal `{literal}` is out of range"
+        ))),
+        Err(_) => Err(RuntimeError::syntax_error(&format!(
+            "Invalid float literal `{literal}`"
+        ))),
+    }
+}
+
+pub fn is_numeric(value: &Value) -> bool {
+    matches!(value, Value::INT(_) | Value::FLOAT(_))
+}
+
+pub fn to_float(value: &Value) -> Option<f64> {
+    match value {
+        Value::INT(value) => Some(*value as f64),
+        Value::FLOAT(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Truncates towards zero, refusing values an `int` cannot represent.
+pub fn float_to_int(value: f64) -> Result<i64, RuntimeError> {
+    // i64::MAX is not representable as f64, so compare against 2^63 exclusively
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if value.is_nan() || !(-LIMIT..LIMIT).contains(&value) {
+        return Err(RuntimeError::arithmetic_error(&format!(
+            "Cannot convert {} to `int`",
+            Value::FLOAT(value)
+        )));
+    }
+
+    Ok(value.trunc() as i64)
+}
+
+pub fn negate(value: &Value) -> Result<Value, RuntimeError> {
+    match value {
+        Value::INT(value) => value.checked_neg().map(Value::INT).ok_or_else(|| {
+            RuntimeError::arithmetic_error(&format!("Integer overflow in `-({value})`"))
+        }),
+        Value::FLOAT(value) => Ok(Value::FLOAT(-value)),
+        _ => Err(RuntimeError::type_error(&format!(
+            "Operator `-` expects a number, found `{}`",
+            value.type_name()
+        ))),
+    }
+}
+
+pub fn compare_numbers(first: &Value, second: &Value) -> Option<Ordering> {
+    match (first, second) {
+        (Value::INT(a), Value::INT(b)) => Some(a.cmp(b)),
+        _ => to_float(first)?.partial_cmp(&to_float(second)?),
+    }
+}
+
+pub fn apply_arithmetic(operator: &str, first: &Value, second: &Value) -> Result<Value, RuntimeError> {
+    match (first, second) {
+        (Value::INT(a), Value::INT(b)) => apply_int_arithmetic(operator, *a, *b),
+        _ => match (to_float(first), to_float(second)) {
+            (Some(a), Some(b)) => apply_float_arithmetic(operator, a, b),
+            _ => Err(RuntimeError::type_error(&format!(
+                "Operator `{operator}` cannot be applied to `{}` and `{}`",
+                first.type_name(),
+                second.type_name()
+            ))),
+        },
+    }
+}
+
+fn apply_int_arithmetic(operator: &str, a: i64, b: i64) -> Result<Value, RuntimeError> {
+    if matches!(operator, "/" | "%") && b == 0 {
+        return Err(division_by_zero());
+    }
+
+    let result = match operator {
+        "+" => a.checked_add(b),
+        "-" => a.checked_sub(b),
+        "*" => a.checked_mul(b),
+        "/" => a.checked_div(b),
+        "%" => a.checked_rem(b),
+        _ => return Err(unknown_operator(operator)),
+    };
+
+    match result {
+        Some(value) => Ok(Value::INT(value)),
+        None => Err(RuntimeError::arithmetic_error(&format!(
+            "Integer overflow in `{a} {operator} {b}`"
+        ))),
+    }
+}
+
+fn apply_float_arithmetic(operator: &str, a: f64, b: f64) -> Result<Value, RuntimeError> {
+    if matches!(operator, "/" | "%") && b == 0.0 {
+        return Err(division_by_zero());
+    }
+
+    match operator {
+        "+" => Ok(Value::FLOAT(a + b)),
+        "-" => Ok(Value::FLOAT(a - b)),
+        "*" => Ok(Value::FLOAT(a * b)),
+        "/" => Ok(Value::FLOAT(a / b)),
+        "%" => Ok(Value::FLOAT(a % b)),
+        _ => Err(unknown_operator(operator)),
+    }
+}
+
+pub fn division_by_zero() -> RuntimeError {
+    RuntimeError::arithmetic_error("Division by zero")
+}
+
+fn unknown_operator(operator: &str) -> RuntimeError {
+    RuntimeError::type_error(&format!("`{operator}` is not an arithmetic operator"))
+}
@@ -0,0 +1,713 @@
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt,
+    rc::Rc,
+};
+
+use crate::{
+    errors::RuntimeError,
+    numeric::compare_numbers,
+    structure::{EnumDeclaration, EnumVariant, ExecutionContext, FunctionDeclaration, Type, TypeKind},
+    tasks::{Task, TaskBlock},
+};
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
+pub enum Value {
+    #[default]
+    NULL,
+    INT(i64),
+    FLOAT(f64),
+    BOOL(bool),
+    STRING(String),
+    ARRAY(Rc<RefCell<ArrayValue>>),
+    MAP(Rc<RefCell<MapValue>>),
+    FUNCTION(Rc<FunctionValue>),
+    ENUM(Rc<EnumValue>),
+    ERROR(Rc<RuntimeError>),
+    FUTURE(Rc<RefCell<FutureValue>>),
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ArrayValue {
+    pub elements: Vec<Value>,
+    pub element_type: Option<Type>,
+}
+
+/// The hashable subset of values that can be used as map keys.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    INT(i64),
+    BOOL(bool),
+    STRING(String),
+}
+
+impl MapKey {
+    pub fn from_value(value: &Value) -> Result<MapKey, RuntimeError> {
+        match value {
+            Value::INT(value) => Ok(MapKey::INT(*value)),
+            Value::BOOL(value) => Ok(MapKey::BOOL(*value)),
+            Value::STRING(value) => Ok(MapKey::STRING(value.clone())),
+            _ => Err(RuntimeError::type_error(&format!(
+                "A value of type `{}` cannot be used as a map key",
+                value.type_name()
+            ))),
+        }
+    }
+}
+
+/// Entries are kept in insertion order; `index` maps each key to its position in `entries`.
+#[derive(Debug, Default, Clone)]
+pub struct MapValue {
+    pub entries: Vec<(Value, Value)>,
+    pub index: HashMap<MapKey, usize>,
+    pub key_type: Option<Type>,
+    pub value_type: Option<Type>,
+}
+
+impl MapValue {
+    pub fn get(&self, key: &Value) -> Result<Option<&Value>, RuntimeError> {
+        let key = MapKey::from_value(key)?;
+        Ok(self.index.get(&key).map(|position| &self.entries[*position].1))
+    }
+
+    pub fn insert(&mut self, key: Value, value: Value) -> Result<(), RuntimeError> {
+        match self.index.get(&MapKey::from_value(&key)?) {
+            Some(position) => self.entries[*position].1 = value,
+            None => {
+                self.index
+                    .insert(MapKey::from_value(&key)?, self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, key: &Value) -> Result<Option<Value>, RuntimeError> {
+        let position = match self.index.remove(&MapKey::from_value(key)?) {
+            Some(position) => position,
+            None => return Ok(None),
+        };
+
+        let (_, value) = self.entries.remove(position);
+        for moved in self.index.values_mut() {
+            if *moved > position {
+                *moved -= 1;
+            }
+        }
+        Ok(Some(value))
+    }
+}
+
+pub struct FunctionValue {
+    pub declaration: FunctionDeclaration,
+    pub closure: Rc<RefCell<ExecutionContext>>,
+    /// The body of an async function, lowered once when it is declared
+    pub task_body: Option<TaskBlock>,
+}
+
+impl FunctionValue {
+    /// `(int, string) -> bool`, the return type is left out when it was not annotated.
+    pub fn signature(&self) -> String {
+        let parameters: Vec<String> = self
+            .declaration
+            .parameter_list
+            .iter()
+            .map(|parameter| parameter.type_def.to_string())
+            .collect();
+
+        match self.declaration.return_type.type_identifier.is_empty() {
+            true => format!("({})", parameters.join(", ")),
+            false => format!("({}) -> {}", parameters.join(", "), self.declaration.return_type),
+        }
+    }
+
+    /// Function types are compared structurally. An unannotated return type matches any
+    /// return type, `bind_type` then gives the function the declared one, which is
+    /// checked when the function returns.
+    fn matches(&self, type_def: &Type) -> bool {
+        let parameters = &self.declaration.parameter_list;
+        let return_type = &self.declaration.return_type;
+
+        parameters.len() == type_def.type_parameters.len()
+            && parameters
+                .iter()
+                .zip(&type_def.type_parameters)
+                .all(|(parameter, expected)| parameter.type_def.to_string() == expected.to_string())
+            && (return_type.type_identifier.is_empty()
+                || type_def
+                    .return_type
+                    .as_ref()
+                    .is_none_or(|expected| return_type.to_string() == expected.to_string()))
+    }
+}
+
+// The closure points back at the scope the function is stored in, so the derived
+// implementation would recurse forever.
+impl fmt::Debug for FunctionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FunctionValue({})", self.declaration.identifier)
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone)]
+pub enum FutureState {
+    PENDING,
+    RUNNING,
+    RESOLVED(Value),
+    REJECTED(RuntimeError),
+}
+
+/// The result of an async call, of `sleep` or of `Future::all` and `Future::race`.
+pub struct FutureValue {
+    pub state: FutureState,
+    /// The body of an async call, while it waits to start or to resume
+    pub task: Option<Task>,
+    /// For a `Future::all` or `Future::race` future, how it settles from `joined`
+    pub join: Option<JoinKind>,
+    pub joined: Vec<Value>,
+    /// How many of the `joined` futures have not settled yet
+    pub unsettled: usize,
+    /// The suspended tasks and the joins to look at again once this future settles
+    pub waiters: Vec<SharedFuture>,
+    pub is_awaited: bool,
+}
+
+impl FutureValue {
+    pub fn pending(task: Task) -> Self {
+        FutureValue {
+            task: Some(task),
+            ..FutureValue::waiting()
+        }
+    }
+
+    /// A future the event loop settles, as opposed to one running a task
+    pub fn waiting() -> Self {
+        FutureValue {
+            state: FutureState::PENDING,
+            task: None,
+            join: None,
+            joined: Vec::new(),
+            unsettled: 0,
+            waiters: Vec::new(),
+            is_awaited: false,
+        }
+    }
+
+    pub fn is_settled(&self) -> bool {
+        matches!(self.state, FutureState::RESOLVED(_) | FutureState::REJECTED(_))
+    }
+
+    /// A failure of an awaited future is handled by whoever awaits it, and so is the
+    /// failure of each future a join settles from.
+    pub fn mark_awaited(&mut self) {
+        self.is_awaited = true;
+        for joined in &self.joined {
+            if let Value::FUTURE(future) = joined {
+                future.borrow_mut().mark_awaited();
+            }
+        }
+    }
+}
+
+impl fmt::Debug for FutureValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FutureValue")
+            .field("state", &self.state)
+            .field("is_awaited", &self.is_awaited)
+            .finish_non_exhaustive()
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy)]
+pub enum JoinKind {
+    ALL,
+    RACE,
+}
+
+pub type SharedFuture = Rc<RefCell<FutureValue>>;
+
+/// Single-threaded cooperative scheduler. `clock` is the virtual time in milliseconds, it
+/// only moves forward when no task is ready and the earliest timer fires.
+#[derive(Debug, Default)]
+pub struct EventLoop {
+    pub clock: u64,
+    /// Tasks to start or resume, in the order they became ready
+    pub ready: VecDeque<SharedFuture>,
+    /// How many tasks are suspended in an `await`, each is a waiter of the future it awaits
+    pub suspended: usize,
+    /// `sleep` futures by the time they resolve at, then by the order they were created
+    pub timers: BTreeMap<(u64, usize), SharedFuture>,
+    pub timers_created: usize,
+    /// Tasks and joins that failed, reported once the program is done unless something
+    /// awaited them
+    pub rejected: Vec<SharedFuture>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct EnumValue {
+    pub enum_identifier: String,
+    pub variant: String,
+    pub payload: Vec<Value>,
+}
+
+/// `enum Result { Ok(any), Err(any) }`, declared in every global scope. Native functions
+/// that can fail on ordinary input return it instead of throwing.
+pub fn result_declaration() -> EnumDeclaration {
+    let variant = |identifier: &str| EnumVariant {
+        identifier: identifier.to_string(),
+        payload_types: vec![Type {
+            type_identifier: String::from("any"),
+            ..Default::default()
+        }],
+    };
+
+    EnumDeclaration {
+        identifier: String::from("Result"),
+        variants: vec![variant("Ok"), variant("Err")],
+        ..Default::default()
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Variable {
+    pub value: Value,
+    pub type_def: Option<Type>,
+    pub is_constant: bool,
+}
+
+/// Non-local exits that unwind through `execute_*` and `solve_*`.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone)]
+pub enum Interrupt {
+    RETURN(Value),
+    ERROR(RuntimeError),
+}
+
+impl From<RuntimeError> for Interrupt {
+    fn from(error: RuntimeError) -> Self {
+        Interrupt::ERROR(error)
+    }
+}
+
+impl Value {
+    pub fn new_array(elements: Vec<Value>) -> Value {
+        Value::ARRAY(Rc::new(RefCell::new(ArrayValue {
+            elements,
+            element_type: None,
+        })))
+    }
+
+    pub fn new_map(entries: Vec<(Value, Value)>) -> Result<Value, RuntimeError> {
+        let mut map = MapValue::default();
+        for (key, value) in entries {
+            map.insert(key, value)?;
+        }
+        Ok(Value::MAP(Rc::new(RefCell::new(map))))
+    }
+
+    /// `Result::Ok(value)`, or `Result::Err(error)` with the error as a value
+    pub fn new_result(result: Result<Value, RuntimeError>) -> Value {
+        let (variant, payload) = match result {
+            Ok(value) => ("Ok", value),
+            Err(error) => ("Err", Value::ERROR(Rc::new(error))),
+        };
+        Value::ENUM(Rc::new(EnumValue {
+            enum_identifier: String::from("Result"),
+            variant: variant.to_string(),
+            payload: vec![payload],
+        }))
+    }
+
+    pub fn type_name(&self) -> String {
+        match self {
+            Value::NULL => String::from("null"),
+            Value::INT(_) => String::from("int"),
+            Value::FLOAT(_) => String::from("float"),
+            Value::BOOL(_) => String::from("bool"),
+            Value::STRING(_) => String::from("string"),
+            Value::ARRAY(array) => {
+                let array = array.borrow();
+                match &array.element_type {
+                    Some(element_type) => format!("{element_type}[]"),
+                    None => format!("{}[]", untyped_element_name(&array.elements)),
+                }
+            }
+            Value::MAP(map) => match (&map.borrow().key_type, &map.borrow().value_type) {
+                (Some(key_type), Some(value_type)) => format!("Map<{key_type}|{value_type}>"),
+                _ => String::from("Map"),
+            },
+            Value::FUNCTION(function) => function.signature(),
+            Value::ENUM(value) => value.enum_identifier.clone(),
+            Value::ERROR(error) => error.kind_name().to_string(),
+            Value::FUTURE(_) => String::from("Future"),
+        }
+    }
+
+    /// Like `Display`, but strings are quoted. Used for values nested in collections.
+    pub fn repr(&self) -> String {
+        match self {
+            Value::STRING(value) => format!("{value:?}"),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, &mut Vec::new())
+    }
+}
+
+impl Value {
+    /// `enclosing` holds the arrays and maps being written around this value, so one that
+    /// contains itself is written as `[...]` or `{...}` where it repeats.
+    fn write(&self, f: &mut fmt::Formatter<'_>, enclosing: &mut Vec<*const ()>) -> fmt::Result {
+        match self {
+            Value::NULL => write!(f, "null"),
+            Value::INT(value) => write!(f, "{value}"),
+            // `Debug` keeps the fractional part of whole floats (`2.0`, not `2`)
+            Value::FLOAT(value) => write!(f, "{value:?}"),
+            Value::BOOL(value) => write!(f, "{value}"),
+            Value::STRING(value) => write!(f, "{value}"),
+            Value::ARRAY(array) => {
+                let pointer = Rc::as_ptr(array) as *const ();
+                if enclosing.contains(&pointer) {
+                    return write!(f, "[...]");
+                }
+                enclosing.push(pointer);
+                write!(f, "[")?;
+                for (position, element) in array.borrow().elements.iter().enumerate() {
+                    if position > 0 {
+                        write!(f, ", ")?;
+                    }
+                    element.write_nested(f, enclosing)?;
+                }
+                enclosing.pop();
+                write!(f, "]")
+            }
+            Value::MAP(map) => {
+                let pointer = Rc::as_ptr(map) as *const ();
+                if enclosing.contains(&pointer) {
+                    return write!(f, "{{...}}");
+                }
+                enclosing.push(pointer);
+                write!(f, "{{")?;
+                for (position, (key, value)) in map.borrow().entries.iter().enumerate() {
+                    if position > 0 {
+                        write!(f, ", ")?;
+                    }
+                    key.write_nested(f, enclosing)?;
+                    write!(f, ": ")?;
+                    value.write_nested(f, enclosing)?;
+                }
+                enclosing.pop();
+                write!(f, "}}")
+            }
+            Value::FUNCTION(function) => {
+                write!(f, "<function {}>", function.declaration.identifier)
+            }
+            Value::ENUM(value) => {
+                write!(f, "{}::{}", value.enum_identifier, value.variant)?;
+                if !value.payload.is_empty() {
+                    write!(f, "(")?;
+                    for (position, payload) in value.payload.iter().enumerate() {
+                        if position > 0 {
+                            write!(f, ", ")?;
+                        }
+                        payload.write_nested(f, enclosing)?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            Value::ERROR(error) => write!(f, "{}: {}", error.kind_name(), error.message),
+            Value::FUTURE(future) => match &future.borrow().state {
+                FutureState::RESOLVED(value) => {
+                    write!(f, "<Future resolved ")?;
+                    value.write_nested(f, enclosing)?;
+                    write!(f, ">")
+                }
+                FutureState::REJECTED(error) => write!(f, "<Future rejected {}>", error.kind_name()),
+                _ => write!(f, "<Future pending>"),
+            },
+        }
+    }
+
+    /// Like `write`, but strings are quoted, see `repr`
+    fn write_nested(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        enclosing: &mut Vec<*const ()>,
+    ) -> fmt::Result {
+        match self {
+            Value::STRING(value) => write!(f, "{value:?}"),
+            _ => self.write(f, enclosing),
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.type_kind {
+            TypeKind::TYPEARRAY => match &self.element_type {
+                Some(element_type) => write!(f, "{element_type}[]"),
+                None => write!(f, "{}[]", self.type_identifier),
+            },
+            TypeKind::GENERIC => {
+                let parameters: Vec<String> =
+                    self.type_parameters.iter().map(|t| t.to_string()).collect();
+                write!(f, "{}<{}>", self.type_identifier, parameters.join("|"))
+            }
+            TypeKind::FUNCTION => {
+                let parameters: Vec<String> =
+                    self.type_parameters.iter().map(|t| t.to_string()).collect();
+                match &self.return_type {
+                    Some(return_type) => write!(f, "({}) -> {return_type}", parameters.join(", ")),
+                    None => write!(f, "({})", parameters.join(", ")),
+                }
+            }
+            _ => write!(f, "{}", self.type_identifier),
+        }
+    }
+}
+
+/// An array without a declared type is named after its elements, `any` when they differ
+/// or there are none. Nested untyped arrays are not looked into, since one can contain
+/// itself.
+fn untyped_element_name(elements: &[Value]) -> String {
+    let mut names = elements.iter().map(|element| match element {
+        Value::ARRAY(array) if array.borrow().element_type.is_none() => String::from("any[]"),
+        _ => element.type_name(),
+    });
+    match names.next() {
+        Some(first) if names.all(|name| name == first) => first,
+        _ => String::from("any"),
+    }
+}
+
+pub fn values_equal(first: &Value, second: &Value) -> bool {
+    equal_values(first, second, &mut Vec::new())
+}
+
+/// Addresses of two arrays or two maps
+type ComparedPair = (*const (), *const ());
+
+/// `comparing` holds the arrays and maps compared further up. An array can contain itself,
+/// a pair met again is part of such a cycle and is taken as equal, its other elements
+/// decide.
+fn equal_values(first: &Value, second: &Value, comparing: &mut Vec<ComparedPair>) -> bool {
+    match (first, second) {
+        (Value::NULL, Value::NULL) => true,
+        (Value::INT(_) | Value::FLOAT(_), Value::INT(_) | Value::FLOAT(_)) => {
+            compare_numbers(first, second) == Some(Ordering::Equal)
+        }
+        (Value::BOOL(a), Value::BOOL(b)) => a == b,
+        (Value::STRING(a), Value::STRING(b)) => a == b,
+        (Value::ARRAY(a), Value::ARRAY(b)) => {
+            let pair = (Rc::as_ptr(a) as *const (), Rc::as_ptr(b) as *const ());
+            if Rc::ptr_eq(a, b) || comparing.contains(&pair) {
+                return true;
+            }
+            comparing.push(pair);
+            let (a, b) = (a.borrow(), b.borrow());
+            let is_equal = a.elements.len() == b.elements.len()
+                && a.elements
+                    .iter()
+                    .zip(b.elements.iter())
+                    .all(|(x, y)| equal_values(x, y, comparing));
+            comparing.pop();
+            is_equal
+        }
+        (Value::MAP(a), Value::MAP(b)) => {
+            let pair = (Rc::as_ptr(a) as *const (), Rc::as_ptr(b) as *const ());
+            if Rc::ptr_eq(a, b) || comparing.contains(&pair) {
+                return true;
+            }
+            comparing.push(pair);
+            let (a, b) = (a.borrow(), b.borrow());
+            let is_equal = a.entries.len() == b.entries.len()
+                && a.entries.iter().all(|(key, value)| match b.get(key) {
+                    Ok(Some(other)) => equal_values(value, other, comparing),
+                    _ => false,
+                });
+            comparing.pop();
+            is_equal
+        }
+        (Value::FUNCTION(a), Value::FUNCTION(b)) => Rc::ptr_eq(a, b),
+        (Value::ERROR(a), Value::ERROR(b)) => Rc::ptr_eq(a, b),
+        (Value::FUTURE(a), Value::FUTURE(b)) => Rc::ptr_eq(a, b),
+        (Value::ENUM(a), Value::ENUM(b)) => {
+            a.enum_identifier == b.enum_identifier
+                && a.variant == b.variant
+                && a.payload.len() == b.payload.len()
+                && a.payload
+                    .iter()
+                    .zip(b.payload.iter())
+                    .all(|(x, y)| equal_values(x, y, comparing))
+        }
+        _ => false,
+    }
+}
+
+/// Natural ordering used by comparison operators and `Array.sort`.
+pub fn compare_values(first: &Value, second: &Value) -> Result<Ordering, RuntimeError> {
+    match (first, second) {
+        (Value::INT(_) | Value::FLOAT(_), Value::INT(_) | Value::FLOAT(_)) => {
+            compare_numbers(first, second).ok_or_else(|| {
+                RuntimeError::arithmetic_error("Cannot compare `NaN` with another number")
+            })
+        }
+        (Value::STRING(a), Value::STRING(b)) => Ok(a.cmp(b)),
+        (Value::BOOL(a), Value::BOOL(b)) => Ok(a.cmp(b)),
+        _ => Err(RuntimeError::type_error(&format!(
+            "Cannot compare `{}` with `{}`",
+            first.type_name(),
+            second.type_name()
+        ))),
+    }
+}
+
+/// The element type of `T[]` and `Array<T>`, if `type_def` describes an array.
+pub fn element_type_of(type_def: &Type) -> Option<Type> {
+    match type_def.type_kind {
+        TypeKind::TYPEARRAY => type_def.element_type.as_deref().cloned(),
+        TypeKind::GENERIC if type_def.type_identifier == "Array" => {
+            type_def.type_parameters.first().cloned()
+        }
+        _ => None,
+    }
+}
+
+/// The key and value types of `Map<K|V>`, if `type_def` describes a map.
+pub fn map_types_of(type_def: &Type) -> Option<(Type, Type)> {
+    match (&type_def.type_kind, type_def.type_parameters.as_slice()) {
+        (TypeKind::GENERIC, [key_type, value_type]) if type_def.type_identifier == "Map" => {
+            Some((key_type.clone(), value_type.clone()))
+        }
+        _ => None,
+    }
+}
+
+pub fn type_matches(value: &Value, type_def: &Type) -> bool {
+    if let Some((key_type, value_type)) = map_types_of(type_def) {
+        return match value {
+            Value::MAP(map) => map.borrow().entries.iter().all(|(key, value)| {
+                type_matches(key, &key_type) && type_matches(value, &value_type)
+            }),
+            _ => false,
+        };
+    }
+
+    if let Some(element_type) = element_type_of(type_def) {
+        return match value {
+            Value::ARRAY(array) => array
+                .borrow()
+                .elements
+                .iter()
+                .all(|element| type_matches(element, &element_type)),
+            _ => false,
+        };
+    }
+
+    match type_def.type_kind {
+        TypeKind::GENERIC => match type_def.type_identifier.as_str() {
+            // the payload is only known once the future settles, see `Interpreter::await_value`
+            "Future" => matches!(value, Value::FUTURE(_)),
+            "Nullable" => {
+                matches!(value, Value::NULL)
+                    || type_def
+                        .type_parameters
+                        .first()
+                        .is_none_or(|inner| type_matches(value, inner))
+            }
+            "Result" => match value {
+                Value::ENUM(value) if value.enum_identifier == "Result" => {
+                    let position = usize::from(value.variant == "Err");
+                    match (value.payload.first(), type_def.type_parameters.get(position)) {
+                        (Some(payload), Some(expected)) => type_matches(payload, expected),
+                        _ => true,
+                    }
+                }
+                _ => false,
+            },
+            // user generics are not checked yet
+            _ => true,
+        },
+        TypeKind::FUNCTION => match value {
+            Value::FUNCTION(function) => function.matches(type_def),
+            _ => false,
+        },
+        _ => match type_def.type_identifier.as_str() {
+            "int" => matches!(value, Value::INT(_)),
+            "bool" => matches!(value, Value::BOOL(_)),
+            "string" => matches!(value, Value::STRING(_)),
+            // `int` values widen to `float`, see `bind_type`
+            "float" => matches!(value, Value::FLOAT(_) | Value::INT(_)),
+            "void" => matches!(value, Value::NULL),
+            "Array" => matches!(value, Value::ARRAY(_)),
+            "Map" => matches!(value, Value::MAP(_)),
+            "any" => true,
+            // class and interface names are not checked yet
+            identifier => match value {
+                Value::ENUM(value) => value.enum_identifier == identifier,
+                Value::ERROR(error) => error.is_instance_of(identifier),
+                _ => true,
+            },
+        },
+    }
+}
+
+/// Converts a value that already passed `type_matches` into its declared type: `int`
+/// widens to `float`, and collections record their declared element (or key and value)
+/// types so later writes can be checked.
+pub fn bind_type(value: Value, type_def: &Type) -> Value {
+    // The elements are bound without holding the borrow, an array may contain itself
+    if let (Value::ARRAY(array), Some(element_type)) = (&value, element_type_of(type_def)) {
+        let elements = std::mem::take(&mut array.borrow_mut().elements);
+        let elements = elements
+            .into_iter()
+            .map(|element| bind_type(element, &element_type))
+            .collect();
+        let mut array = array.borrow_mut();
+        array.elements = elements;
+        if array.element_type.is_none() {
+            array.element_type = Some(element_type);
+        }
+    }
+
+    if let (Value::MAP(map), Some((key_type, value_type))) = (&value, map_types_of(type_def)) {
+        let mut entries = std::mem::take(&mut map.borrow_mut().entries);
+        for (_, entry) in entries.iter_mut() {
+            *entry = bind_type(std::mem::take(entry), &value_type);
+        }
+        let mut map = map.borrow_mut();
+        map.entries = entries;
+        if map.key_type.is_none() {
+            map.key_type = Some(key_type);
+            map.value_type = Some(value_type);
+        }
+    }
+
+    if let (Value::FUNCTION(function), Some(return_type)) = (&value, &type_def.return_type) {
+        if function.declaration.return_type.type_identifier.is_empty() {
+            let mut declaration = function.declaration.clone();
+            declaration.return_type = (**return_type).clone();
+            return Value::FUNCTION(Rc::new(FunctionValue {
+                declaration,
+                closure: function.closure.clone(),
+                task_body: function.task_body.clone(),
+            }));
+        }
+    }
+
+    match (value, type_def.type_identifier.as_str()) {
+        (Value::INT(value), "float") => Value::FLOAT(value as f64),
+        (value, _) => value,
+    }
+}
@@ -0,0 +1,270 @@
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+
+use crate::{
+    errors::RuntimeError,
+    interpreter::Interpreter,
+    runtime::{
+        bind_type, compare_values, type_matches, values_equal, ArrayValue, Interrupt, Value,
+    },
+    standard_library::expect_position,
+};
+
+pub struct ArrayModule {}
+impl ArrayModule {
+    pub fn get_property(
+        array: &Rc<RefCell<ArrayValue>>,
+        identifier: &str,
+    ) -> Result<Value, RuntimeError> {
+        match identifier {
+            "length" => Ok(Value::INT(array.borrow().elements.len() as i64)),
+            _ => Err(RuntimeError::type_error(&format!(
+                "`Array` has no property `{identifier}`"
+            ))),
+        }
+    }
+
+    pub fn get_index(
+        array: &Rc<RefCell<ArrayValue>>,
+        index: &Value,
+    ) -> Result<Value, RuntimeError> {
+        let array = array.borrow();
+        let position = Self::check_index(&array, index)?;
+        Ok(array.elements[position].clone())
+    }
+
+    pub fn set_index(
+        array: &Rc<RefCell<ArrayValue>>,
+        index: &Value,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        let position = Self::check_index(&array.borrow(), index)?;
+        let value = Self::check_element(array, value)?;
+        array.borrow_mut().elements[position] = value;
+        Ok(())
+    }
+
+    pub fn call_method(
+        interpreter: &mut Interpreter,
+        array: &Rc<RefCell<ArrayValue>>,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt> {
+        match identifier {
+            "push" => {
+                expect_arguments(identifier, &arguments, 1, usize::MAX)?;
+                for argument in arguments {
+                    let argument = Self::check_element(array, argument)?;
+                    array.borrow_mut().elements.push(argument);
+                }
+                Ok(Value::INT(array.borrow().elements.len() as i64))
+            }
+            "pop" => {
+                expect_arguments(identifier, &arguments, 0, 0)?;
+                match array.borrow_mut().elements.pop() {
+                    Some(value) => Ok(value),
+                    None => {
+                        Err(RuntimeError::index_error("Cannot `pop` from an empty array").into())
+                    }
+                }
+            }
+            "map" => {
+                expect_arguments(identifier, &arguments, 1, 1)?;
+                let elements = array.borrow().elements.clone();
+                let mut mapped = Vec::with_capacity(elements.len());
+                for element in elements {
+                    mapped.push(interpreter.call_function(&arguments[0], vec![element])?);
+                }
+                Ok(Value::new_array(mapped))
+            }
+            "filter" => {
+                expect_arguments(identifier, &arguments, 1, 1)?;
+                let elements = array.borrow().elements.clone();
+                let mut filtered = Vec::new();
+                for element in elements {
+                    match interpreter.call_function(&arguments[0], vec![element.clone()])? {
+                        Value::BOOL(true) => filtered.push(element),
+                        Value::BOOL(false) => {}
+                        value => return Err(predicate_error(identifier, &value).into()),
+                    }
+                }
+                Ok(Value::new_array(filtered))
+            }
+            "reduce" => {
+                expect_arguments(identifier, &arguments, 1, 2)?;
+                let mut elements = array.borrow().elements.clone().into_iter();
+                let mut accumulator = match arguments.get(1) {
+                    Some(initial) => initial.clone(),
+                    None => match elements.next() {
+                        Some(first) => first,
+                        None => {
+                            return Err(RuntimeError::index_error(
+                                "Cannot `reduce` an empty array without an initial value",
+                            )
+                            .into())
+                        }
+                    },
+                };
+                for element in elements {
+                    accumulator =
+                        interpreter.call_function(&arguments[0], vec![accumulator, element])?;
+                }
+                Ok(accumulator)
+            }
+            "sort" => {
+                expect_arguments(identifier, &arguments, 0, 1)?;
+                let mut elements = array.borrow().elements.clone();
+                let mut error = None;
+
+                // `sort_by` cannot fail, so the first error is kept aside and reported afterwards
+                elements.sort_by(|a, b| {
+                    if error.is_some() {
+                        return Ordering::Equal;
+                    }
+                    let ordering = match arguments.first() {
+                        Some(comparator) => {
+                            match interpreter.call_function(comparator, vec![a.clone(), b.clone()])
+                            {
+                                Ok(Value::INT(value)) => Ok(value.cmp(&0)),
+                                Ok(value) => {
+                                    Err(Interrupt::from(RuntimeError::type_error(&format!(
+                                        "The `sort` comparator must return `int`, found `{}`",
+                                        value.type_name()
+                                    ))))
+                                }
+                                Err(interrupt) => Err(interrupt),
+                            }
+                        }
+                        None => compare_values(a, b).map_err(Interrupt::from),
+                    };
+                    ordering.unwrap_or_else(|interrupt| {
+                        error = Some(interrupt);
+                        Ordering::Equal
+                    })
+                });
+
+                if let Some(error) = error {
+                    return Err(error);
+                }
+                array.borrow_mut().elements = elements;
+                Ok(Value::ARRAY(array.clone()))
+            }
+            "slice" => {
+                expect_arguments(identifier, &arguments, 1, 2)?;
+                let array = array.borrow();
+                let length = array.elements.len();
+                let start = expect_position(identifier, &arguments[0], length, "an array")?;
+                let end = match arguments.get(1) {
+                    Some(end) => expect_position(identifier, end, length, "an array")?,
+                    None => length,
+                };
+                if start > end {
+                    return Err(RuntimeError::index_error(&format!(
+                        "`slice` start {start} is greater than end {end}"
+                    ))
+                    .into());
+                }
+                Ok(Value::ARRAY(Rc::new(RefCell::new(ArrayValue {
+                    elements: array.elements[start..end].to_vec(),
+                    element_type: array.element_type.clone(),
+                }))))
+            }
+            "contains" => {
+                expect_arguments(identifier, &arguments, 1, 1)?;
+                let found = array
+                    .borrow()
+                    .elements
+                    .iter()
+                    .any(|element| values_equal(element, &arguments[0]));
+                Ok(Value::BOOL(found))
+            }
+            "join" => {
+                expect_arguments(identifier, &arguments, 0, 1)?;
+                let separator = match arguments.first() {
+                    Some(Value::STRING(separator)) => separator.clone(),
+                    Some(value) => {
+                        return Err(RuntimeError::type_error(&format!(
+                            "`join` expects a `string` separator, found `{}`",
+                            value.type_name()
+                        ))
+                        .into())
+                    }
+                    None => String::new(),
+                };
+                let parts: Vec<String> = array
+                    .borrow()
+                    .elements
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect();
+                Ok(Value::STRING(parts.join(&separator)))
+            }
+            _ => Err(
+                RuntimeError::type_error(&format!("`Array` has no method `{identifier}`")).into(),
+            ),
+        }
+    }
+
+    fn check_index(array: &ArrayValue, index: &Value) -> Result<usize, RuntimeError> {
+        match index {
+            Value::INT(position)
+                if *position >= 0 && (*position as usize) < array.elements.len() =>
+            {
+                Ok(*position as usize)
+            }
+            Value::INT(position) => Err(RuntimeError::index_error(&format!(
+                "Index {position} is out of bounds for an array of length {}",
+                array.elements.len()
+            ))),
+            _ => Err(RuntimeError::type_error(&format!(
+                "Array indices must be `int`, found `{}`",
+                index.type_name()
+            ))),
+        }
+    }
+
+    /// Runs without borrowing the array, which the value may be or contain
+    fn check_element(array: &Rc<RefCell<ArrayValue>>, value: Value) -> Result<Value, RuntimeError> {
+        let element_type = array.borrow().element_type.clone();
+        match &element_type {
+            Some(element_type) if !type_matches(&value, element_type) => {
+                Err(RuntimeError::type_error(&format!(
+                    "Cannot add a value of type `{}` to an array of `{element_type}`",
+                    value.type_name()
+                )))
+            }
+            Some(element_type) => Ok(bind_type(value, element_type)),
+            None => Ok(value),
+        }
+    }
+}
+
+pub fn expect_arguments(
+    identifier: &str,
+    arguments: &[Value],
+    min: usize,
+    max: usize,
+) -> Result<(), RuntimeError> {
+    if arguments.len() >= min && arguments.len() <= max {
+        return Ok(());
+    }
+
+    let expected = if min == max {
+        min.to_string()
+    } else if max == usize::MAX {
+        format!("at least {min}")
+    } else {
+        format!("{min} to {max}")
+    };
+
+    Err(RuntimeError::type_error(&format!(
+        "`{identifier}` expects {expected} argument(s), found {}",
+        arguments.len()
+    )))
+}
+
+fn predicate_error(identifier: &str, value: &Value) -> RuntimeError {
+    RuntimeError::type_error(&format!(
+        "The `{identifier}` callback must return `bool`, found `{}`",
+        value.type_name()
+    ))
+}
@@ -1,96 +1,149 @@
-use crate::structure::{Argument, ExpressionKind, ArgumentKind, ExecutionContext};
+use std::io::{self, BufRead, Write};
+
+use crate::{
+    errors::RuntimeError,
+    interpreter::Interpreter,
+    runtime::{Interrupt, Value},
+    standard_library::{undefined_member, NativeFunction, NativeModule},
+};
+
+const FUNCTIONS: &[NativeFunction] = &[
+    NativeFunction {
+        name: "print",
+        parameters: &["any"],
+        is_variadic: true,
+        return_type: "void",
+    },
+    NativeFunction {
+        name: "println",
+        parameters: &["any"],
+        is_variadic: true,
+        return_type: "void",
+    },
+    NativeFunction {
+        name: "error",
+        parameters: &["any"],
+        is_variadic: true,
+        return_type: "void",
+    },
+    NativeFunction {
+        name: "eprintln",
+        parameters: &["any"],
+        is_variadic: true,
+        return_type: "void",
+    },
+    NativeFunction {
+        name: "format",
+        parameters: &["string", "any"],
+        is_variadic: true,
+        return_type: "string",
+    },
+    NativeFunction {
+        name: "readLine",
+        parameters: &[],
+        is_variadic: false,
+        return_type: "Nullable<string>",
+    },
+];
 
 pub struct ConsoleModule {}
 impl ConsoleModule {
-    pub fn print(arguments: Vec<Argument>, execution_context: &ExecutionContext) {
-        for arg in arguments {
-            let mut value = String::new();
-            match arg.argument_kind {
-                ArgumentKind::EXPRESSION => {
-                    let expression = arg.expression.unwrap();
-                    match expression.expression_kind {
-                        ExpressionKind::UNARY => {
-                            // let variable_name = expression
-                            //     .unary_expression
-                            //     .unwrap()
-                            //     .string_value
-                            //     .unwrap()
-                            //     .replace('"', "");
+    /// What `print` and friends write: the arguments separated by a space
+    pub fn join(arguments: &[Value]) -> String {
+        let parts: Vec<String> = arguments.iter().map(|value| value.to_string()).collect();
+        parts.join(" ")
+    }
+
+    /// Fills each `{}` of the template with the next value, `{{` and `}}` stand for
+    /// literal braces. The values must match the placeholders one for one.
+    pub fn format(template: &str, values: &[Value]) -> Result<String, RuntimeError> {
+        let mut output = String::with_capacity(template.len());
+        let mut values = values.iter();
+        let mut placeholders = 0;
+        let mut chars = template.chars().peekable();
+
+        while let Some(char) = chars.next() {
+            match (char, chars.peek()) {
+                ('{', Some('{')) | ('}', Some('}')) => {
+                    chars.next();
+                    output.push(char);
+                }
+                ('{', Some('}')) => {
+                    chars.next();
+                    placeholders += 1;
+                    match values.next() {
+                        Some(value) => output.push_str(&value.to_string()),
+                        None => {
+                            return Err(RuntimeError::value_error(&format!(
+                                "The format string has {placeholders} or more placeholders, but only {} value(s) were given",
+                                placeholders - 1
+                            )))
                         }
-                        ExpressionKind::BINARY => {}
-                        ExpressionKind::MEMBERACCESS => {}
-                        ExpressionKind::PREFIX => {}
-                        ExpressionKind::POSTFIX => {}
                     }
                 }
-                ArgumentKind::IDENTIFIER => {
-                    let identifier_node = execution_context
-                        .scoped_variables
-                        .iter()
-                        .find(|variable| variable.identifier == arg.identifier.clone().unwrap());
-
-                    value = identifier_node
-                        .clone()
-                        .unwrap()
-                        .clone()
-                        .definition
-                        .unwrap()
-                        .unary_expression
-                        .unwrap()
-                        .string_value
-                        .unwrap()
-                        .replace('"', "");
-                }
+                _ => output.push(char),
             }
+        }
 
-            print!("{}", value);
+        let extra = values.len();
+        if extra > 0 {
+            return Err(RuntimeError::value_error(&format!(
+                "The format string has {placeholders} placeholder(s), but {} value(s) were given",
+                placeholders + extra
+            )));
         }
-        
-    }
 
-    pub fn println(arguments: Vec<Argument>, execution_context: &ExecutionContext) {
-        for arg in arguments {
-            let mut value = String::new();
-            match arg.argument_kind {
-                ArgumentKind::EXPRESSION => {
-                    let expression = arg.expression.unwrap();
-                    match expression.expression_kind {
-                        ExpressionKind::UNARY => {
-                            // let variable_name = expression
-                            //     .unary_expression
-                            //     .unwrap()
-                            //     .string_value
-                            //     .unwrap()
-                            //     .replace('"', "");
-                        }
-                        ExpressionKind::BINARY => {}
-                        ExpressionKind::MEMBERACCESS => {}
-                        ExpressionKind::PREFIX => {}
-                        ExpressionKind::POSTFIX => {}
-                    }
-                }
-                ArgumentKind::IDENTIFIER => {
-                    let identifier_node = execution_context
-                        .scoped_variables
-                        .iter()
-                        .find(|variable| variable.identifier == arg.identifier.clone().unwrap());
+        Ok(output)
+    }
 
-                    value = identifier_node
-                        .clone()
-                        .unwrap()
-                        .clone()
-                        .definition
-                        .unwrap()
-                        .unary_expression
-                        .unwrap()
-                        .string_value
-                        .unwrap()
-                        .replace('"', "");
-                }
+    /// The next line of standard input without its line break, `null` at the end of input.
+    pub fn read_line() -> Result<Value, RuntimeError> {
+        let mut line = String::new();
+        match io::stdin().lock().read_line(&mut line) {
+            Ok(0) => Ok(Value::NULL),
+            Ok(_) => {
+                let line = line.strip_suffix('\n').unwrap_or(&line);
+                Ok(Value::STRING(line.strip_suffix('\r').unwrap_or(line).to_string()))
             }
+            Err(error) => Err(RuntimeError::io_error(&format!("Cannot read from stdin: {error}"))),
+        }
+    }
+}
 
-            println!("{}", value);
+impl NativeModule for ConsoleModule {
+    fn name(&self) -> &'static str {
+        "Console"
+    }
+
+    fn path(&self) -> &'static str {
+        "std.io.Console"
+    }
+
+    fn functions(&self) -> &'static [NativeFunction] {
+        FUNCTIONS
+    }
+
+    fn call(
+        &self,
+        _interpreter: &mut Interpreter,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt> {
+        match (identifier, arguments.as_slice()) {
+            ("print", _) => {
+                print!("{}", Self::join(&arguments));
+                // A prompt has to show up before `readLine` waits for input
+                let _ = io::stdout().flush();
+            }
+            ("println", _) => println!("{}", Self::join(&arguments)),
+            ("error" | "eprintln", _) => eprintln!("{}", Self::join(&arguments)),
+            ("format", [Value::STRING(template), values @ ..]) => {
+                return Ok(Value::STRING(Self::format(template, values)?))
+            }
+            ("readLine", _) => return Ok(Self::read_line()?),
+            _ => return Err(undefined_member(self, self.name(), identifier).into()),
         }
-        
+
+        Ok(Value::NULL)
     }
 }
@@ -0,0 +1,64 @@
+use std::env;
+
+use crate::{
+    errors::RuntimeError,
+    interpreter::Interpreter,
+    runtime::{Interrupt, Value},
+    standard_library::{NativeFunction, NativeModule},
+};
+
+const FUNCTIONS: &[NativeFunction] = &[
+    NativeFunction {
+        name: "args",
+        parameters: &[],
+        is_variadic: false,
+        return_type: "string[]",
+    },
+    NativeFunction {
+        name: "get",
+        parameters: &["string"],
+        is_variadic: false,
+        return_type: "Nullable<string>",
+    },
+];
+
+pub struct EnvModule {}
+
+impl NativeModule for EnvModule {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    fn path(&self) -> &'static str {
+        "std.env"
+    }
+
+    fn functions(&self) -> &'static [NativeFunction] {
+        FUNCTIONS
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt> {
+        match (identifier, arguments.as_slice()) {
+            // The arguments after the program path, `ws main.ws a b` gives `["a", "b"]`
+            ("args", _) => {
+                let arguments = interpreter.program_arguments.iter().cloned().map(Value::STRING);
+                Ok(Value::new_array(arguments.collect()))
+            }
+            // `null` when the variable is not set
+            ("get", [Value::STRING(name)]) => {
+                if !interpreter.allow_process {
+                    return Err(RuntimeError::permission_denied("env::get").into());
+                }
+                Ok(env::var_os(name).map_or(Value::NULL, |value| {
+                    Value::STRING(value.to_string_lossy().into_owned())
+                }))
+            }
+            _ => Err(RuntimeError::undefined(&format!("env::{identifier}")).into()),
+        }
+    }
+}
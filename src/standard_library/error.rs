@@ -0,0 +1,18 @@
+use crate::{errors::RuntimeError, runtime::Value};
+
+pub struct ErrorModule {}
+impl ErrorModule {
+    pub fn get_property(error: &RuntimeError, identifier: &str) -> Result<Value, RuntimeError> {
+        match identifier {
+            "name" => Ok(Value::STRING(error.kind_name().to_string())),
+            "message" => Ok(Value::STRING(error.message.clone())),
+            "stack" => Ok(Value::new_array(
+                error.stack.iter().cloned().map(Value::STRING).collect(),
+            )),
+            _ => Err(RuntimeError::type_error(&format!(
+                "`{}` has no property `{identifier}`",
+                error.kind_name()
+            ))),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{
+    errors::RuntimeError,
+    interpreter::Interpreter,
+    runtime::{Interrupt, Value},
+    standard_library::{undefined_member, NativeFunction, NativeModule},
+};
+
+const FUNCTIONS: &[NativeFunction] = &[
+    NativeFunction {
+        name: "readText",
+        parameters: &["string"],
+        is_variadic: false,
+        return_type: "Result<string|IOError>",
+    },
+    NativeFunction {
+        name: "writeText",
+        parameters: &["string", "string"],
+        is_variadic: false,
+        return_type: "Result<void|IOError>",
+    },
+    NativeFunction {
+        name: "append",
+        parameters: &["string", "string"],
+        is_variadic: false,
+        return_type: "Result<void|IOError>",
+    },
+    NativeFunction {
+        name: "exists",
+        parameters: &["string"],
+        is_variadic: false,
+        return_type: "bool",
+    },
+    NativeFunction {
+        name: "listDir",
+        parameters: &["string"],
+        is_variadic: false,
+        return_type: "Result<string[]|IOError>",
+    },
+    NativeFunction {
+        name: "mkdir",
+        parameters: &["string"],
+        is_variadic: false,
+        return_type: "Result<void|IOError>",
+    },
+    NativeFunction {
+        name: "remove",
+        parameters: &["string"],
+        is_variadic: false,
+        return_type: "Result<void|IOError>",
+    },
+    NativeFunction {
+        name: "readLines",
+        parameters: &["string"],
+        is_variadic: false,
+        return_type: "Result<string[]|IOError>",
+    },
+];
+
+/// Relative paths are resolved against the working directory of the interpreter, like
+/// the path of the program itself. Failures are `Result::Err(IOError)` values.
+pub struct FsModule {}
+impl FsModule {
+    fn read_text(path: &str) -> Result<Value, RuntimeError> {
+        fs::read_to_string(path)
+            .map(Value::STRING)
+            .map_err(|error| io_error("read", path, error))
+    }
+
+    /// Creates the file, or replaces its contents
+    fn write_text(path: &str, text: &str) -> Result<Value, RuntimeError> {
+        fs::write(path, text)
+            .map(|_| Value::NULL)
+            .map_err(|error| io_error("write", path, error))
+    }
+
+    /// Creates the file when it does not exist yet
+    fn append(path: &str, text: &str) -> Result<Value, RuntimeError> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(text.as_bytes()))
+            .map(|_| Value::NULL)
+            .map_err(|error| io_error("append to", path, error))
+    }
+
+    /// Entry names, sorted
+    fn list_dir(path: &str) -> Result<Value, RuntimeError> {
+        let mut names = fs::read_dir(path)
+            .and_then(|entries| {
+                entries
+                    .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+                    .collect::<io::Result<Vec<String>>>()
+            })
+            .map_err(|error| io_error("list", path, error))?;
+        names.sort();
+        Ok(Value::new_array(names.into_iter().map(Value::STRING).collect()))
+    }
+
+    /// Creates missing parent directories too, an existing directory is not an error
+    fn mkdir(path: &str) -> Result<Value, RuntimeError> {
+        fs::create_dir_all(path)
+            .map(|_| Value::NULL)
+            .map_err(|error| io_error("create directory", path, error))
+    }
+
+    /// Removes a file or an empty directory
+    fn remove(path: &str) -> Result<Value, RuntimeError> {
+        let result = match Path::new(path).is_dir() {
+            true => fs::remove_dir(path),
+            false => fs::remove_file(path),
+        };
+        result
+            .map(|_| Value::NULL)
+            .map_err(|error| io_error("remove", path, error))
+    }
+
+    /// Lines without their `\n` or `\r\n` line breaks
+    fn read_lines(path: &str) -> Result<Value, RuntimeError> {
+        let text = fs::read_to_string(path).map_err(|error| io_error("read", path, error))?;
+        let lines = text.lines().map(|line| Value::STRING(line.to_string())).collect();
+        Ok(Value::new_array(lines))
+    }
+}
+
+impl NativeModule for FsModule {
+    fn name(&self) -> &'static str {
+        "fs"
+    }
+
+    fn path(&self) -> &'static str {
+        "std.fs"
+    }
+
+    fn functions(&self) -> &'static [NativeFunction] {
+        FUNCTIONS
+    }
+
+    fn call(
+        &self,
+        _interpreter: &mut Interpreter,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt> {
+        let strings: Vec<&str> = arguments
+            .iter()
+            .map(|argument| match argument {
+                Value::STRING(string) => string.as_str(),
+                _ => "",
+            })
+            .collect();
+        let path = strings[0];
+
+        let result = match identifier {
+            "readText" => Self::read_text(path),
+            "writeText" => Self::write_text(path, strings[1]),
+            "append" => Self::append(path, strings[1]),
+            "exists" => return Ok(Value::BOOL(Path::new(path).exists())),
+            "listDir" => Self::list_dir(path),
+            "mkdir" => Self::mkdir(path),
+            "remove" => Self::remove(path),
+            "readLines" => Self::read_lines(path),
+            _ => return Err(undefined_member(self, self.name(), identifier).into()),
+        };
+        Ok(Value::new_result(result))
+    }
+}
+
+fn io_error(action: &str, path: &str, error: io::Error) -> RuntimeError {
+    RuntimeError {
+        identifier: Some(path.to_string()),
+        ..RuntimeError::io_error(&format!("Cannot {action} `{path}`: {error}"))
+    }
+}
@@ -0,0 +1,203 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    errors::RuntimeError,
+    interpreter::Interpreter,
+    runtime::{FutureState, FutureValue, Interrupt, JoinKind, Value},
+    standard_library::{array::expect_arguments, NativeFunction, NativeModule},
+};
+
+const FUNCTIONS: &[NativeFunction] = &[
+    NativeFunction {
+        name: "all",
+        parameters: &["Array"],
+        is_variadic: false,
+        return_type: "Future",
+    },
+    NativeFunction {
+        name: "race",
+        parameters: &["Array"],
+        is_variadic: false,
+        return_type: "Future",
+    },
+    NativeFunction {
+        name: "now",
+        parameters: &[],
+        is_variadic: false,
+        return_type: "int",
+    },
+];
+
+pub struct FutureModule {}
+impl FutureModule {
+    /// `sleep(ms)`: a future that resolves `ms` milliseconds later on the virtual clock.
+    pub fn sleep(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+        expect_arguments("sleep", &arguments, 1, 1)?;
+        let duration = match arguments[0] {
+            Value::INT(duration) if duration >= 0 => duration as u64,
+            ref value => {
+                return Err(RuntimeError::type_error(&format!(
+                    "`sleep` expects a non-negative `int` duration, found {}",
+                    value.repr()
+                )))
+            }
+        };
+
+        let future = Rc::new(RefCell::new(FutureValue::waiting()));
+        let event_loop = &mut interpreter.event_loop;
+        let wakes_at = event_loop.clock + duration;
+        event_loop.timers.insert((wakes_at, event_loop.timers_created), future.clone());
+        event_loop.timers_created += 1;
+        Ok(Value::FUTURE(future))
+    }
+
+    pub fn call_method(
+        interpreter: &mut Interpreter,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt> {
+        match identifier {
+            "all" => Self::join(interpreter, JoinKind::ALL, arguments),
+            "race" => Self::join(interpreter, JoinKind::RACE, arguments),
+            "now" => {
+                expect_arguments(identifier, &arguments, 0, 0)?;
+                Ok(Value::INT(interpreter.event_loop.clock as i64))
+            }
+            _ => Err(RuntimeError::undefined(&format!("Future::{identifier}")).into()),
+        }
+    }
+
+    /// `Future::all` settles once every future has, with the array of their values, or like
+    /// the first one to fail. `Future::race` settles like the first future to settle, the
+    /// earliest in the array on a tie. Plain values count as already resolved, and every task
+    /// in the array keeps running on the event loop until it is done.
+    fn join(
+        interpreter: &mut Interpreter,
+        kind: JoinKind,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt> {
+        let identifier = match kind {
+            JoinKind::ALL => "all",
+            JoinKind::RACE => "race",
+        };
+        expect_arguments(identifier, &arguments, 1, 1)?;
+        let futures = match &arguments[0] {
+            Value::ARRAY(array) => array.borrow().elements.clone(),
+            value => {
+                return Err(RuntimeError::type_error(&format!(
+                    "`{identifier}` expects an array of futures, found `{}`",
+                    value.type_name()
+                ))
+                .into())
+            }
+        };
+        if let (JoinKind::RACE, true) = (kind, futures.is_empty()) {
+            return Err(RuntimeError::index_error("`race` expects at least one future").into());
+        }
+
+        // A failure of the futures is handled by whoever awaits the join, see `mark_awaited`
+        let future = Rc::new(RefCell::new(FutureValue {
+            join: Some(kind),
+            joined: futures,
+            ..FutureValue::waiting()
+        }));
+        let outcome = Self::join_outcome(&future.borrow());
+        match outcome {
+            Some(state) => {
+                if let FutureState::REJECTED(_) = state {
+                    interpreter.event_loop.rejected.push(future.clone());
+                }
+                future.borrow_mut().state = state;
+            }
+            None => {
+                let mut join = future.borrow_mut();
+                let join = &mut *join;
+                for joined in &join.joined {
+                    if let Value::FUTURE(joined) = joined {
+                        let mut joined = joined.borrow_mut();
+                        if !joined.is_settled() {
+                            joined.waiters.push(future.clone());
+                            join.unsettled += 1;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(Value::FUTURE(future))
+    }
+
+    /// How a join settles once one of its futures settled as `state`, `None` while it has
+    /// to wait. The whole array is only looked at again once every future settled.
+    pub fn joined_settled(join: &mut FutureValue, state: &FutureState) -> Option<FutureState> {
+        if join.is_settled() {
+            return None;
+        }
+        join.unsettled -= 1;
+        match (join.join?, state) {
+            (JoinKind::RACE, _) | (JoinKind::ALL, FutureState::REJECTED(_)) => Some(state.clone()),
+            (JoinKind::ALL, _) if join.unsettled == 0 => Self::join_outcome(join),
+            (JoinKind::ALL, _) => None,
+        }
+    }
+
+    /// How a `Future::all` or `Future::race` future settles, `None` while it has to wait.
+    pub fn join_outcome(future: &FutureValue) -> Option<FutureState> {
+        let mut outcomes = future.joined.iter().map(|element| match element {
+            Value::FUTURE(future) => {
+                let future = future.borrow();
+                future.is_settled().then(|| future.state.clone())
+            }
+            value => Some(FutureState::RESOLVED(value.clone())),
+        });
+
+        match future.join? {
+            JoinKind::RACE => outcomes.find_map(|outcome| outcome),
+            JoinKind::ALL => {
+                let outcomes: Vec<Option<FutureState>> = outcomes.collect();
+                let rejection = outcomes
+                    .iter()
+                    .flatten()
+                    .find(|state| matches!(state, FutureState::REJECTED(_)));
+                if let Some(rejection) = rejection {
+                    return Some(rejection.clone());
+                }
+
+                let values = outcomes
+                    .into_iter()
+                    .map(|outcome| match outcome {
+                        Some(FutureState::RESOLVED(value)) => Some(value),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<Value>>>()?;
+                Some(FutureState::RESOLVED(Value::new_array(values)))
+            }
+        }
+    }
+}
+
+impl NativeModule for FutureModule {
+    fn name(&self) -> &'static str {
+        "Future"
+    }
+
+    fn path(&self) -> &'static str {
+        "std.async.Future"
+    }
+
+    fn functions(&self) -> &'static [NativeFunction] {
+        FUNCTIONS
+    }
+
+    fn is_prelude(&self) -> bool {
+        true
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt> {
+        Self::call_method(interpreter, identifier, arguments)
+    }
+}
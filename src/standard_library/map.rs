@@ -0,0 +1,106 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    errors::RuntimeError,
+    runtime::{bind_type, type_matches, MapValue, Value},
+    standard_library::array::expect_arguments,
+};
+
+pub struct MapModule {}
+impl MapModule {
+    pub fn get_property(map: &Rc<RefCell<MapValue>>, identifier: &str) -> Result<Value, RuntimeError> {
+        match identifier {
+            "length" => Ok(Value::INT(map.borrow().entries.len() as i64)),
+            _ => Err(RuntimeError::type_error(&format!(
+                "`Map` has no property `{identifier}`"
+            ))),
+        }
+    }
+
+    pub fn get_index(map: &Rc<RefCell<MapValue>>, key: &Value) -> Result<Value, RuntimeError> {
+        match map.borrow().get(key)? {
+            Some(value) => Ok(value.clone()),
+            None => Err(RuntimeError::key_error(&format!(
+                "Key {} does not exist in the map",
+                key.repr()
+            ))),
+        }
+    }
+
+    pub fn set_index(map: &Rc<RefCell<MapValue>>, key: Value, value: Value) -> Result<(), RuntimeError> {
+        let value = Self::check_entry(map, &key, value)?;
+        map.borrow_mut().insert(key, value)
+    }
+
+    pub fn call_method(
+        map: &Rc<RefCell<MapValue>>,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        match identifier {
+            "keys" => {
+                expect_arguments(identifier, &arguments, 0, 0)?;
+                let keys = map.borrow().entries.iter().map(|(key, _)| key.clone()).collect();
+                Ok(Value::new_array(keys))
+            }
+            "values" => {
+                expect_arguments(identifier, &arguments, 0, 0)?;
+                let values = map
+                    .borrow()
+                    .entries
+                    .iter()
+                    .map(|(_, value)| value.clone())
+                    .collect();
+                Ok(Value::new_array(values))
+            }
+            "entries" => {
+                expect_arguments(identifier, &arguments, 0, 0)?;
+                let entries = map
+                    .borrow()
+                    .entries
+                    .iter()
+                    .map(|(key, value)| Value::new_array(vec![key.clone(), value.clone()]))
+                    .collect();
+                Ok(Value::new_array(entries))
+            }
+            "has" => {
+                expect_arguments(identifier, &arguments, 1, 1)?;
+                Ok(Value::BOOL(map.borrow().get(&arguments[0])?.is_some()))
+            }
+            "remove" => {
+                expect_arguments(identifier, &arguments, 1, 1)?;
+                Ok(Value::BOOL(map.borrow_mut().remove(&arguments[0])?.is_some()))
+            }
+            _ => Err(RuntimeError::type_error(&format!("`Map` has no method `{identifier}`"))),
+        }
+    }
+
+    /// Runs without borrowing the map, which the value may be or contain
+    fn check_entry(map: &Rc<RefCell<MapValue>>, key: &Value, value: Value) -> Result<Value, RuntimeError> {
+        let (key_type, value_type) = {
+            let map = map.borrow();
+            (map.key_type.clone(), map.value_type.clone())
+        };
+
+        if let Some(key_type) = &key_type {
+            if !type_matches(key, key_type) {
+                return Err(RuntimeError::type_error(&format!(
+                    "Cannot use a key of type `{}` in a map with `{key_type}` keys",
+                    key.type_name()
+                )));
+            }
+        }
+
+        if let Some(value_type) = &value_type {
+            if !type_matches(&value, value_type) {
+                return Err(RuntimeError::type_error(&format!(
+                    "Cannot store a value of type `{}` in a map with `{value_type}` values",
+                    value.type_name()
+                )));
+            }
+            return Ok(bind_type(value, value_type));
+        }
+
+        Ok(value)
+    }
+}
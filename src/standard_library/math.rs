@@ -0,0 +1,303 @@
+use std::{cmp::Ordering, f64::consts};
+
+use crate::{
+    errors::RuntimeError,
+    interpreter::Interpreter,
+    numeric::{compare_numbers, division_by_zero, float_to_int, negate, to_float},
+    runtime::{Interrupt, Value},
+    standard_library::{undefined_member, NativeFunction, NativeModule},
+};
+
+const FUNCTIONS: &[NativeFunction] = &[
+    NativeFunction {
+        name: "abs",
+        parameters: &["number"],
+        is_variadic: false,
+        return_type: "number",
+    },
+    NativeFunction {
+        name: "min",
+        parameters: &["number", "number"],
+        is_variadic: true,
+        return_type: "number",
+    },
+    NativeFunction {
+        name: "max",
+        parameters: &["number", "number"],
+        is_variadic: true,
+        return_type: "number",
+    },
+    NativeFunction {
+        name: "pow",
+        parameters: &["number", "number"],
+        is_variadic: false,
+        return_type: "number",
+    },
+    NativeFunction {
+        name: "sqrt",
+        parameters: &["number"],
+        is_variadic: false,
+        return_type: "float",
+    },
+    NativeFunction {
+        name: "floor",
+        parameters: &["number"],
+        is_variadic: false,
+        return_type: "int",
+    },
+    NativeFunction {
+        name: "ceil",
+        parameters: &["number"],
+        is_variadic: false,
+        return_type: "int",
+    },
+    NativeFunction {
+        name: "round",
+        parameters: &["number"],
+        is_variadic: false,
+        return_type: "int",
+    },
+    NativeFunction {
+        name: "sin",
+        parameters: &["number"],
+        is_variadic: false,
+        return_type: "float",
+    },
+    NativeFunction {
+        name: "cos",
+        parameters: &["number"],
+        is_variadic: false,
+        return_type: "float",
+    },
+    NativeFunction {
+        name: "tan",
+        parameters: &["number"],
+        is_variadic: false,
+        return_type: "float",
+    },
+    NativeFunction {
+        name: "asin",
+        parameters: &["number"],
+        is_variadic: false,
+        return_type: "float",
+    },
+    NativeFunction {
+        name: "acos",
+        parameters: &["number"],
+        is_variadic: false,
+        return_type: "float",
+    },
+    NativeFunction {
+        name: "atan",
+        parameters: &["number"],
+        is_variadic: false,
+        return_type: "float",
+    },
+    NativeFunction {
+        name: "atan2",
+        parameters: &["number", "number"],
+        is_variadic: false,
+        return_type: "float",
+    },
+    NativeFunction {
+        name: "clamp",
+        parameters: &["number", "number", "number"],
+        is_variadic: false,
+        return_type: "number",
+    },
+    NativeFunction {
+        name: "gcd",
+        parameters: &["int", "int"],
+        is_variadic: false,
+        return_type: "int",
+    },
+    NativeFunction {
+        name: "mod",
+        parameters: &["int", "int"],
+        is_variadic: false,
+        return_type: "int",
+    },
+];
+
+/// `int` results stay `int` wherever the operation allows it. Overflow, and a `float`
+/// result that is `NaN` or infinite, is an `ArithmeticError`.
+pub struct MathModule {}
+impl MathModule {
+    fn abs(value: &Value) -> Result<Value, RuntimeError> {
+        match value {
+            Value::INT(number) if *number < 0 => negate(value),
+            Value::FLOAT(number) => Ok(Value::FLOAT(number.abs())),
+            _ => Ok(value.clone()),
+        }
+    }
+
+    /// The first of the smallest (or largest) values, returned as it was given.
+    fn extreme(arguments: Vec<Value>, wanted: Ordering) -> Value {
+        let mut arguments = arguments.into_iter();
+        let mut extreme = arguments.next().unwrap();
+        for argument in arguments {
+            if compare_numbers(&argument, &extreme) == Some(wanted) {
+                extreme = argument;
+            }
+        }
+        extreme
+    }
+
+    fn pow(base: &Value, exponent: &Value) -> Result<Value, RuntimeError> {
+        match (base, exponent) {
+            (Value::INT(base), Value::INT(exponent)) if *exponent >= 0 => {
+                u32::try_from(*exponent)
+                    .ok()
+                    .and_then(|exponent| base.checked_pow(exponent))
+                    .map(Value::INT)
+                    .ok_or_else(|| {
+                        RuntimeError::arithmetic_error(&format!(
+                            "Integer overflow in `pow({base}, {exponent})`"
+                        ))
+                    })
+            }
+            _ => finite("pow", &[base.clone(), exponent.clone()], float(base).powf(float(exponent))),
+        }
+    }
+
+    fn sqrt(value: &Value) -> Result<Value, RuntimeError> {
+        let number = float(value);
+        if number < 0.0 {
+            return Err(RuntimeError::arithmetic_error(&format!(
+                "Cannot take the square root of the negative number {value}"
+            )));
+        }
+        Ok(Value::FLOAT(number.sqrt()))
+    }
+
+    fn to_int(value: &Value, rounding: fn(f64) -> f64) -> Result<Value, RuntimeError> {
+        match value {
+            Value::FLOAT(number) => Ok(Value::INT(float_to_int(rounding(*number))?)),
+            _ => Ok(value.clone()),
+        }
+    }
+
+    fn clamp(value: Value, low: Value, high: Value) -> Result<Value, RuntimeError> {
+        if compare_numbers(&low, &high) == Some(Ordering::Greater) {
+            return Err(RuntimeError::arithmetic_error(&format!(
+                "`clamp` expects a lower bound no greater than the upper bound, found {low} and {high}"
+            )));
+        }
+
+        Ok(match (compare_numbers(&value, &low), compare_numbers(&value, &high)) {
+            (Some(Ordering::Less), _) => low,
+            (_, Some(Ordering::Greater)) => high,
+            _ => value,
+        })
+    }
+
+    fn gcd(a: i64, b: i64) -> Result<Value, RuntimeError> {
+        let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        i64::try_from(a).map(Value::INT).map_err(|_| {
+            RuntimeError::arithmetic_error("Integer overflow in `gcd`, the result is 2^63")
+        })
+    }
+
+    /// Euclidean remainder, never negative: `mod(-7, 3)` is `2`.
+    fn modulo(a: i64, b: i64) -> Result<Value, RuntimeError> {
+        if b == 0 {
+            return Err(division_by_zero());
+        }
+        a.checked_rem_euclid(b).map(Value::INT).ok_or_else(|| {
+            RuntimeError::arithmetic_error(&format!("Integer overflow in `mod({a}, {b})`"))
+        })
+    }
+}
+
+impl NativeModule for MathModule {
+    fn name(&self) -> &'static str {
+        "math"
+    }
+
+    fn path(&self) -> &'static str {
+        "std.math"
+    }
+
+    fn functions(&self) -> &'static [NativeFunction] {
+        FUNCTIONS
+    }
+
+    fn get_property(&self, identifier: &str) -> Option<Value> {
+        match identifier {
+            "PI" => Some(Value::FLOAT(consts::PI)),
+            "E" => Some(Value::FLOAT(consts::E)),
+            _ => None,
+        }
+    }
+
+    fn call(
+        &self,
+        _interpreter: &mut Interpreter,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt> {
+        let result = match identifier {
+            "abs" => Self::abs(&arguments[0])?,
+            "min" => Self::extreme(arguments, Ordering::Less),
+            "max" => Self::extreme(arguments, Ordering::Greater),
+            "pow" => Self::pow(&arguments[0], &arguments[1])?,
+            "sqrt" => Self::sqrt(&arguments[0])?,
+            "floor" => Self::to_int(&arguments[0], f64::floor)?,
+            "ceil" => Self::to_int(&arguments[0], f64::ceil)?,
+            "round" => Self::to_int(&arguments[0], f64::round)?,
+            "sin" => finite(identifier, &arguments, float(&arguments[0]).sin())?,
+            "cos" => finite(identifier, &arguments, float(&arguments[0]).cos())?,
+            "tan" => finite(identifier, &arguments, float(&arguments[0]).tan())?,
+            "asin" => finite(identifier, &arguments, float(&arguments[0]).asin())?,
+            "acos" => finite(identifier, &arguments, float(&arguments[0]).acos())?,
+            "atan" => finite(identifier, &arguments, float(&arguments[0]).atan())?,
+            "atan2" => {
+                let result = float(&arguments[0]).atan2(float(&arguments[1]));
+                finite(identifier, &arguments, result)?
+            }
+            "clamp" => {
+                let mut arguments = arguments.into_iter();
+                let (value, low, high) = (
+                    arguments.next().unwrap(),
+                    arguments.next().unwrap(),
+                    arguments.next().unwrap(),
+                );
+                Self::clamp(value, low, high)?
+            }
+            "gcd" => Self::gcd(int(&arguments[0]), int(&arguments[1]))?,
+            "mod" => Self::modulo(int(&arguments[0]), int(&arguments[1]))?,
+            _ => return Err(undefined_member(self, self.name(), identifier).into()),
+        };
+
+        Ok(result)
+    }
+}
+
+/// `asin(2)` is `NaN` and `pow(10.0, 400)` is infinite, neither is a usable number
+fn finite(identifier: &str, arguments: &[Value], result: f64) -> Result<Value, RuntimeError> {
+    if result.is_finite() {
+        return Ok(Value::FLOAT(result));
+    }
+
+    let arguments: Vec<String> = arguments.iter().map(|argument| argument.to_string()).collect();
+    Err(RuntimeError::arithmetic_error(&format!(
+        "`math::{identifier}({})` has no finite result",
+        arguments.join(", ")
+    )))
+}
+
+// The signatures already guarantee the argument types
+
+fn float(value: &Value) -> f64 {
+    to_float(value).unwrap_or_default()
+}
+
+fn int(value: &Value) -> i64 {
+    match value {
+        Value::INT(value) => *value,
+        _ => 0,
+    }
+}
@@ -1 +1,251 @@
-pub mod console;
\ No newline at end of file
+use std::{fmt, rc::Rc};
+
+use crate::{
+    errors::{edit_distance, RuntimeError},
+    interpreter::Interpreter,
+    runtime::{Interrupt, Value},
+    standard_library::{
+        console::ConsoleModule, env::EnvModule, fs::FsModule, future::FutureModule,
+        math::MathModule, process::ProcessModule, string::StringModule,
+    },
+};
+
+pub mod array;
+pub mod console;
+pub mod env;
+pub mod error;
+pub mod fs;
+pub mod future;
+pub mod map;
+pub mod math;
+pub mod number;
+pub mod process;
+pub mod string;
+
+/// The signature of a function of a native module. Parameter types are `int`, `float`,
+/// `string`, `bool`, `number`, `Array`, `Map`, `function` or `any`.
+pub struct NativeFunction {
+    pub name: &'static str,
+    pub parameters: &'static [&'static str],
+    /// The last parameter takes any number of arguments, none included
+    pub is_variadic: bool,
+    pub return_type: &'static str,
+}
+
+impl NativeFunction {
+    /// `println(...any) -> void`
+    pub fn signature(&self) -> String {
+        let mut parameters: Vec<String> =
+            self.parameters.iter().map(|parameter| parameter.to_string()).collect();
+        if let (true, Some(last)) = (self.is_variadic, parameters.last_mut()) {
+            last.insert_str(0, "...");
+        }
+        format!("{}({}) -> {}", self.name, parameters.join(", "), self.return_type)
+    }
+
+    fn check_arguments(&self, module: &str, arguments: &[Value]) -> Result<(), RuntimeError> {
+        let required = self.parameters.len() - usize::from(self.is_variadic);
+        let arity_matches = match self.is_variadic {
+            true => arguments.len() >= required,
+            false => arguments.len() == required,
+        };
+        if !arity_matches {
+            return Err(RuntimeError::type_error(&format!(
+                "`{module}::{}` expects {required}{} argument(s), found {}",
+                self.name,
+                if self.is_variadic { " or more" } else { "" },
+                arguments.len()
+            ))
+            .with_suggestion(&format!("The signature is `{module}::{}`", self.signature())));
+        }
+
+        for (position, argument) in arguments.iter().enumerate() {
+            let parameter = self.parameters[position.min(self.parameters.len() - 1)];
+            if !accepts(parameter, argument) {
+                return Err(RuntimeError::type_error(&format!(
+                    "`{module}::{}` expects `{parameter}` for argument {}, found `{}`",
+                    self.name,
+                    position + 1,
+                    argument.type_name()
+                ))
+                .with_suggestion(&format!("The signature is `{module}::{}`", self.signature())));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn accepts(parameter: &str, value: &Value) -> bool {
+    match (parameter, value) {
+        ("any", _) => true,
+        ("number", Value::INT(_) | Value::FLOAT(_)) => true,
+        ("Array", Value::ARRAY(_)) => true,
+        ("Map", Value::MAP(_)) => true,
+        ("function", Value::FUNCTION(_)) => true,
+        (parameter, value) => parameter == value.type_name(),
+    }
+}
+
+/// A module implemented in Rust, imported by its path like a `.ws` module.
+pub trait NativeModule {
+    /// The name the module is used with, `Console`
+    fn name(&self) -> &'static str;
+
+    /// The full import path, `std.io.Console`
+    fn path(&self) -> &'static str;
+
+    fn functions(&self) -> &'static [NativeFunction];
+
+    /// Modules such as `Future` are available without an import
+    fn is_prelude(&self) -> bool {
+        false
+    }
+
+    /// Constants such as `math::PI`
+    fn get_property(&self, _identifier: &str) -> Option<Value> {
+        None
+    }
+
+    /// Runs one of `functions()`, the arguments already match its signature
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt>;
+}
+
+/// Every native module, consulted by imports and by `Module::member` calls.
+pub struct NativeRegistry {
+    modules: Vec<Rc<dyn NativeModule>>,
+}
+
+impl Default for NativeRegistry {
+    fn default() -> Self {
+        let mut registry = NativeRegistry {
+            modules: Vec::new(),
+        };
+        registry.register(Rc::new(ConsoleModule {}));
+        registry.register(Rc::new(FutureModule {}));
+        registry.register(Rc::new(MathModule {}));
+        registry.register(Rc::new(StringModule {}));
+        registry.register(Rc::new(FsModule {}));
+        registry.register(Rc::new(EnvModule {}));
+        registry.register(Rc::new(ProcessModule {}));
+        registry
+    }
+}
+
+impl fmt::Debug for NativeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.modules.iter().map(|module| module.path()))
+            .finish()
+    }
+}
+
+impl NativeRegistry {
+    pub fn register(&mut self, module: Rc<dyn NativeModule>) {
+        self.modules.push(module);
+    }
+
+    pub fn find(&self, path: &str) -> Option<Rc<dyn NativeModule>> {
+        self.modules.iter().find(|module| module.path() == path).cloned()
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<Rc<dyn NativeModule>> {
+        self.modules.iter().find(|module| module.name() == name).cloned()
+    }
+
+    /// The registered path closest to a path that matched nothing, if any is close enough.
+    /// A path ending with the name of a module, like `foo.Console`, is always close.
+    pub fn suggest(&self, path: &str) -> Option<&'static str> {
+        let name = path.rsplit('.').next().unwrap_or(path);
+
+        self.modules
+            .iter()
+            .map(|module| {
+                let distance = match module.name().eq_ignore_ascii_case(name) {
+                    true => 0,
+                    false => edit_distance(path, module.path()),
+                };
+                (module.path(), distance)
+            })
+            .filter(|(candidate, distance)| *distance <= (candidate.len() / 4).max(2))
+            .min_by_key(|(_, distance)| *distance)
+            .map(|(candidate, _)| candidate)
+    }
+
+    /// Checks the arguments against the function signature before running it.
+    pub fn call(
+        interpreter: &mut Interpreter,
+        module: &dyn NativeModule,
+        local_name: &str,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt> {
+        let function = module
+            .functions()
+            .iter()
+            .find(|function| function.name == identifier)
+            .ok_or_else(|| undefined_member(module, local_name, identifier))?;
+
+        function.check_arguments(local_name, &arguments)?;
+        module.call(interpreter, identifier, arguments)
+    }
+
+    pub fn get_property(
+        module: &dyn NativeModule,
+        local_name: &str,
+        identifier: &str,
+    ) -> Result<Value, RuntimeError> {
+        match module.get_property(identifier) {
+            Some(value) => Ok(value),
+            None if module.functions().iter().any(|function| function.name == identifier) => {
+                Err(RuntimeError::type_error(&format!(
+                    "`{local_name}::{identifier}` is a function and must be called"
+                )))
+            }
+            None => Err(undefined_member(module, local_name, identifier)),
+        }
+    }
+}
+
+/// A position from 0 to `length` included, as taken by `slice` and `substring`.
+/// `container` names what the position is in for the error, such as `an array`.
+pub fn expect_position(
+    identifier: &str,
+    value: &Value,
+    length: usize,
+    container: &str,
+) -> Result<usize, RuntimeError> {
+    match value {
+        Value::INT(position) if *position >= 0 && (*position as usize) <= length => {
+            Ok(*position as usize)
+        }
+        Value::INT(position) => Err(RuntimeError::index_error(&format!(
+            "`{identifier}` position {position} is out of bounds for {container} of length {length}"
+        ))),
+        _ => Err(RuntimeError::type_error(&format!(
+            "`{identifier}` expects `int` positions, found `{}`",
+            value.type_name()
+        ))),
+    }
+}
+
+/// `math::sqr` is undefined, suggesting the closest function of the module if any is close.
+pub fn undefined_member(module: &dyn NativeModule, local_name: &str, identifier: &str) -> RuntimeError {
+    let error = RuntimeError::undefined(&format!("{local_name}::{identifier}"));
+    let closest = module
+        .functions()
+        .iter()
+        .map(|function| (function.name, edit_distance(identifier, function.name)))
+        .filter(|(name, distance)| *distance <= (name.len() / 3).max(1))
+        .min_by_key(|(_, distance)| *distance);
+
+    match closest {
+        Some((name, _)) => error.with_suggestion(&format!("Did you mean `{local_name}::{name}`?")),
+        None => error,
+    }
+}
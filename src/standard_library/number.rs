@@ -0,0 +1,33 @@
+use crate::{
+    errors::RuntimeError,
+    numeric::{float_to_int, to_float},
+    runtime::Value,
+    standard_library::array::expect_arguments,
+};
+
+pub struct NumberModule {}
+impl NumberModule {
+    pub fn call_method(
+        value: &Value,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        match identifier {
+            "toInt" => {
+                expect_arguments(identifier, &arguments, 0, 0)?;
+                match value {
+                    Value::FLOAT(value) => Ok(Value::INT(float_to_int(*value)?)),
+                    _ => Ok(value.clone()),
+                }
+            }
+            "toFloat" => {
+                expect_arguments(identifier, &arguments, 0, 0)?;
+                Ok(Value::FLOAT(to_float(value).unwrap_or_default()))
+            }
+            _ => Err(RuntimeError::type_error(&format!(
+                "`{}` has no method `{identifier}`",
+                value.type_name()
+            ))),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    io::{self, Write},
+    process::{self, Command},
+};
+
+use crate::{
+    errors::RuntimeError,
+    interpreter::Interpreter,
+    runtime::{Interrupt, Value},
+    standard_library::{NativeFunction, NativeModule},
+};
+
+const FUNCTIONS: &[NativeFunction] = &[
+    NativeFunction {
+        name: "exit",
+        parameters: &["int"],
+        is_variadic: false,
+        return_type: "void",
+    },
+    NativeFunction {
+        name: "run",
+        parameters: &["string", "Array"],
+        is_variadic: false,
+        return_type: "Result<Map<string|any>|IOError>",
+    },
+];
+
+pub struct ProcessModule {}
+impl ProcessModule {
+    /// Ends the program right away, async tasks that were not awaited never run
+    fn exit(code: i64) -> Result<Value, RuntimeError> {
+        let code = i32::try_from(code).map_err(|_| {
+            RuntimeError::value_error(&format!("Exit code {code} is out of range"))
+        })?;
+        let _ = io::stdout().flush();
+        process::exit(code)
+    }
+
+    /// Waits for the command and captures its output. The map holds `stdout`, `stderr` and
+    /// `status`, which is `null` when the command was ended by a signal. A command that
+    /// cannot be started gives `Result::Err(IOError)`, a failing one does not.
+    fn run(command: &str, arguments: &[Value]) -> Result<Value, RuntimeError> {
+        let mut text_arguments = Vec::with_capacity(arguments.len());
+        for argument in arguments {
+            match argument {
+                Value::STRING(argument) => text_arguments.push(argument.as_str()),
+                _ => {
+                    return Err(RuntimeError::type_error(&format!(
+                        "`process::run` expects `string` arguments, found `{}`",
+                        argument.type_name()
+                    )))
+                }
+            }
+        }
+
+        let output = match Command::new(command).args(&text_arguments).output() {
+            Ok(output) => output,
+            Err(error) => {
+                return Ok(Value::new_result(Err(RuntimeError {
+                    identifier: Some(command.to_string()),
+                    ..RuntimeError::io_error(&format!("Cannot run `{command}`: {error}"))
+                })))
+            }
+        };
+
+        let text = |bytes: &[u8]| Value::STRING(String::from_utf8_lossy(bytes).into_owned());
+        let status = output.status.code().map_or(Value::NULL, |code| Value::INT(code.into()));
+        let result = Value::new_map(vec![
+            (Value::STRING(String::from("stdout")), text(&output.stdout)),
+            (Value::STRING(String::from("stderr")), text(&output.stderr)),
+            (Value::STRING(String::from("status")), status),
+        ])?;
+        Ok(Value::new_result(Ok(result)))
+    }
+}
+
+impl NativeModule for ProcessModule {
+    fn name(&self) -> &'static str {
+        "process"
+    }
+
+    fn path(&self) -> &'static str {
+        "std.process"
+    }
+
+    fn functions(&self) -> &'static [NativeFunction] {
+        FUNCTIONS
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt> {
+        match (identifier, arguments.as_slice()) {
+            ("exit", [Value::INT(code)]) => Ok(Self::exit(*code)?),
+            ("run", [Value::STRING(command), Value::ARRAY(command_arguments)]) => {
+                if !interpreter.allow_process {
+                    return Err(RuntimeError::permission_denied("process::run").into());
+                }
+                Ok(Self::run(command, &command_arguments.borrow().elements)?)
+            }
+            _ => Err(RuntimeError::undefined(&format!("process::{identifier}")).into()),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{
+    errors::RuntimeError,
+    interpreter::Interpreter,
+    runtime::{Interrupt, Value},
+    standard_library::{
+        array::expect_arguments, expect_position, undefined_member, NativeFunction, NativeModule,
+    },
+};
+
+const FUNCTIONS: &[NativeFunction] = &[
+    NativeFunction {
+        name: "parseInt",
+        parameters: &["string"],
+        is_variadic: false,
+        return_type: "Result<int|ValueError>",
+    },
+    NativeFunction {
+        name: "parseFloat",
+        parameters: &["string"],
+        is_variadic: false,
+        return_type: "Result<float|ValueError>",
+    },
+];
+
+/// The largest string `repeat` builds, 1 GiB
+const MAX_STRING_SIZE: usize = 1 << 30;
+
+/// Methods of `string` values and the `std.string` module. Lengths and positions count
+/// characters (Unicode scalar values), not bytes.
+pub struct StringModule {}
+impl StringModule {
+    pub fn get_property(string: &str, identifier: &str) -> Result<Value, RuntimeError> {
+        match identifier {
+            "length" => Ok(Value::INT(string.chars().count() as i64)),
+            _ => Err(RuntimeError::type_error(&format!(
+                "`string` has no property `{identifier}`"
+            ))),
+        }
+    }
+
+    pub fn call_method(
+        string: &str,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        match identifier {
+            "toUpper" => {
+                expect_arguments(identifier, &arguments, 0, 0)?;
+                Ok(Value::STRING(string.to_uppercase()))
+            }
+            "toLower" => {
+                expect_arguments(identifier, &arguments, 0, 0)?;
+                Ok(Value::STRING(string.to_lowercase()))
+            }
+            "trim" => {
+                expect_arguments(identifier, &arguments, 0, 0)?;
+                Ok(Value::STRING(string.trim().to_string()))
+            }
+            "split" => {
+                expect_arguments(identifier, &arguments, 1, 1)?;
+                let separator = expect_string(identifier, &arguments[0])?;
+                // An empty separator splits between every character
+                let parts: Vec<Value> = match separator.is_empty() {
+                    true => string.chars().map(|char| Value::STRING(char.to_string())).collect(),
+                    false => string
+                        .split(separator)
+                        .map(|part| Value::STRING(part.to_string()))
+                        .collect(),
+                };
+                Ok(Value::new_array(parts))
+            }
+            // Every occurrence is replaced
+            "replace" => {
+                expect_arguments(identifier, &arguments, 2, 2)?;
+                let pattern = expect_string(identifier, &arguments[0])?;
+                let replacement = expect_string(identifier, &arguments[1])?;
+                if pattern.is_empty() {
+                    return Err(RuntimeError::value_error("`replace` cannot replace an empty string"));
+                }
+                Ok(Value::STRING(string.replace(pattern, replacement)))
+            }
+            "startsWith" => {
+                expect_arguments(identifier, &arguments, 1, 1)?;
+                let prefix = expect_string(identifier, &arguments[0])?;
+                Ok(Value::BOOL(string.starts_with(prefix)))
+            }
+            // The position of the first occurrence, -1 when there is none
+            "indexOf" => {
+                expect_arguments(identifier, &arguments, 1, 1)?;
+                let needle = expect_string(identifier, &arguments[0])?;
+                let position = match string.find(needle) {
+                    Some(byte_position) => string[..byte_position].chars().count() as i64,
+                    None => -1,
+                };
+                Ok(Value::INT(position))
+            }
+            // Characters from `start` up to, not including, `end` (the end of the string
+            // when left out)
+            "substring" => {
+                expect_arguments(identifier, &arguments, 1, 2)?;
+                let length = string.chars().count();
+                let start = expect_position(identifier, &arguments[0], length, "a string")?;
+                let end = match arguments.get(1) {
+                    Some(end) => expect_position(identifier, end, length, "a string")?,
+                    None => length,
+                };
+                if start > end {
+                    return Err(RuntimeError::index_error(&format!(
+                        "`substring` start {start} is after its end {end}"
+                    )));
+                }
+                Ok(Value::STRING(string.chars().skip(start).take(end - start).collect()))
+            }
+            "repeat" => {
+                expect_arguments(identifier, &arguments, 1, 1)?;
+                match &arguments[0] {
+                    Value::INT(count) if *count >= 0 => {
+                        let size = usize::try_from(*count)
+                            .ok()
+                            .and_then(|count| string.len().checked_mul(count))
+                            .filter(|size| *size <= MAX_STRING_SIZE);
+                        match size {
+                            Some(_) => Ok(Value::STRING(string.repeat(*count as usize))),
+                            None => Err(RuntimeError::value_error(&format!(
+                                "`repeat({count})` would make a string longer than {MAX_STRING_SIZE} bytes"
+                            ))),
+                        }
+                    }
+                    Value::INT(count) => Err(RuntimeError::value_error(&format!(
+                        "`repeat` expects a count of zero or more, found {count}"
+                    ))),
+                    value => Err(RuntimeError::type_error(&format!(
+                        "`repeat` expects an `int` count, found `{}`",
+                        value.type_name()
+                    ))),
+                }
+            }
+            "chars" => {
+                expect_arguments(identifier, &arguments, 0, 0)?;
+                let chars = string.chars().map(|char| Value::STRING(char.to_string())).collect();
+                Ok(Value::new_array(chars))
+            }
+            _ => Err(RuntimeError::type_error(&format!(
+                "`string` has no method `{identifier}`"
+            ))),
+        }
+    }
+
+    /// Decimal digits with an optional sign, surrounding whitespace is ignored
+    fn parse_int(text: &str) -> Result<Value, RuntimeError> {
+        let trimmed = text.trim();
+        let digits = trimmed.strip_prefix(['+', '-']).unwrap_or(trimmed);
+        if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(RuntimeError::value_error(&format!("Cannot parse {text:?} as an `int`")));
+        }
+
+        trimmed.parse::<i64>().map(Value::INT).map_err(|_| {
+            RuntimeError::value_error(&format!("{text:?} does not fit in a 64-bit `int`"))
+        })
+    }
+
+    fn parse_float(text: &str) -> Result<Value, RuntimeError> {
+        match text.trim().parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(Value::FLOAT(value)),
+            Ok(_) if text.trim().bytes().any(|byte| byte.is_ascii_digit()) => Err(
+                RuntimeError::value_error(&format!("{text:?} is out of range for a `float`")),
+            ),
+            _ => Err(RuntimeError::value_error(&format!("Cannot parse {text:?} as a `float`"))),
+        }
+    }
+}
+
+impl NativeModule for StringModule {
+    fn name(&self) -> &'static str {
+        "string"
+    }
+
+    fn path(&self) -> &'static str {
+        "std.string"
+    }
+
+    fn functions(&self) -> &'static [NativeFunction] {
+        FUNCTIONS
+    }
+
+    fn call(
+        &self,
+        _interpreter: &mut Interpreter,
+        identifier: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Interrupt> {
+        let text = expect_string(identifier, &arguments[0])?;
+        let result = match identifier {
+            "parseInt" => Self::parse_int(text),
+            "parseFloat" => Self::parse_float(text),
+            _ => return Err(undefined_member(self, self.name(), identifier).into()),
+        };
+        Ok(Value::new_result(result))
+    }
+}
+
+fn expect_string<'a>(identifier: &str, value: &'a Value) -> Result<&'a str, RuntimeError> {
+    match value {
+        Value::STRING(string) => Ok(string),
+        _ => Err(RuntimeError::type_error(&format!(
+            "`{identifier}` expects a `string`, found `{}`",
+            value.type_name()
+        ))),
+    }
+}
@@ -1,71 +1,83 @@
-pub fn unbox<T>(value: Box<T>) -> T {
-    *value
-}
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
-#[derive(Debug, Default, Clone)]
-pub struct ImportedModules {
-    pub modules: Vec<ImportedModule>,
-}
+use crate::{
+    modules::Module,
+    runtime::{Value, Variable},
+};
 
 #[derive(Debug, Default, Clone)]
 pub struct ImportedModule {
     pub parent: String,
     pub name: String,
     pub full_path: String,
+    /// `Out` in `import std.io.Console as Out;`
+    pub alias: Option<String>,
+    /// The names picked by `import utils.math.{sqrt, PI};`, empty for a whole module
+    pub items: Vec<ImportedItem>,
 }
 
-#[derive(Debug, Clone)]
-pub enum MemberAccessKind {
-    PROPERTY,
-    METHOD
+impl ImportedModule {
+    /// The name the module is accessed with in the importing file
+    pub fn local_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ImportedItem {
+    pub identifier: String,
+    pub alias: Option<String>,
 }
 
-impl Default for MemberAccessKind {
-    fn default() -> Self {
-        MemberAccessKind::PROPERTY
+impl ImportedItem {
+    pub fn local_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.identifier)
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
+pub enum MemberAccessKind {
+    #[default]
+    PROPERTY,
+    METHOD
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct MemberAccess {
-    pub parent: String,
+    pub object: Box<Expression>,
     pub identifier: String,
     pub kind: MemberAccessKind,
+    pub is_static: bool,
     pub args_list: Vec<Argument>,
     pub type_arguments: Option<Vec<Type>>,
+    pub line: usize,
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct Argument {
-    pub argument_kind: ArgumentKind,
-    pub expression: Option<Expression>,
+    /// The parameter name of a named argument, `x` in `f(x: 1)`
     pub identifier: Option<String>,
-}
-
-#[derive(Debug, Clone)]
-pub enum ArgumentKind {
-    EXPRESSION,
-    IDENTIFIER
-}
-
-impl Default for ArgumentKind {
-    fn default() -> Self {
-        ArgumentKind::EXPRESSION
-    }
+    pub expression: Expression,
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct FunctionCall {
     pub identifier: String,
     pub type_arguments: Vec<Type>,
-    pub args_list: Vec<Argument>
+    pub args_list: Vec<Argument>,
+    pub line: usize,
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct BinaryExpression {
-    pub first_term: UnaryExpression,
+    pub first_term: Box<Expression>,
     pub operator: String,
-    pub second_term: UnaryExpression,
+    pub second_term: Box<Expression>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -75,68 +87,185 @@ pub struct Expression {
     pub unary_expression: Option<UnaryExpression>,
     pub prefix_expression: Option<AffixExpression>,
     pub postfix_expression: Option<AffixExpression>,
-    pub member_access: Option<MemberAccess>
+    pub member_access: Option<MemberAccess>,
+    pub index_expression: Option<IndexExpression>,
+    pub call_expression: Option<CallExpression>,
+}
+
+impl Expression {
+    /// The name behind a bare identifier expression, which may also be a type or module.
+    pub fn as_identifier(&self) -> Option<&str> {
+        match (&self.expression_kind, &self.unary_expression) {
+            (ExpressionKind::UNARY, Some(unary_expression))
+                if matches!(unary_expression.kind, UnaryExpressionKind::IDENTIFIER) =>
+            {
+                unary_expression.string_value.as_deref()
+            }
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone)]
+pub struct CallExpression {
+    pub callee: Box<Expression>,
+    pub args_list: Vec<Argument>,
+    pub line: usize,
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
 pub enum ExpressionKind {
+    #[default]
     UNARY,
     BINARY,
     PREFIX,
     POSTFIX,
-    MEMBERACCESS
-}
-
-impl Default for ExpressionKind {
-    fn default() -> Self {
-        ExpressionKind::UNARY
-    }
+    MEMBERACCESS,
+    INDEX,
+    CALL,
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct AffixExpression {
     pub operator: String,
-    pub identifier: String
+    pub operand: Box<Expression>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct IndexExpression {
+    pub identifier: String,
+    /// Set when indexing the result of another expression instead of a variable
+    pub object: Option<Box<Expression>>,
+    pub indices: Vec<Expression>,
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct UnaryExpression {
     pub kind: UnaryExpressionKind,
     pub string_value: Option<String>,
+    pub literal: Option<Literal>,
     pub function_call: Option<FunctionCall>,
+    pub lambda: Option<FunctionDeclaration>,
+    pub match_expression: Option<MatchExpression>,
     pub expression: Option<Box<Expression>>
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone)]
+pub struct Literal {
+    pub literal_kind: LiteralKind,
+    pub value: String,
+    /// The value of a number literal, parsed once when the program is read
+    pub number: Option<Value>,
+    pub elements: Vec<Expression>,
+    pub entries: Vec<MapEntry>,
+    pub segments: Vec<StringSegment>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct StringSegment {
+    pub segment_kind: StringSegmentKind,
+    pub text: String,
+    pub expression: Option<Expression>,
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
+pub enum StringSegmentKind {
+    #[default]
+    TEXT,
+    INTERPOLATION,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct MatchExpression {
+    pub expression: Box<Expression>,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub expression: Option<Expression>,
+    pub block: Option<Block>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Pattern {
+    pub pattern_kind: PatternKind,
+    pub identifier: String,
+    pub enum_identifier: String,
+    pub literal: Option<Literal>,
+    pub payload: Vec<Pattern>,
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
+pub enum PatternKind {
+    #[default]
+    WILDCARD,
+    BINDING,
+    LITERAL,
+    VARIANT,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct MapEntry {
+    pub key: Expression,
+    pub value: Expression,
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
+pub enum LiteralKind {
+    #[default]
+    NUMBER,
+    FLOAT,
+    STRING,
+    BOOLEAN,
+    NULL,
+    ARRAY,
+    MAP
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
 pub enum AccessModifier {
     PUBLIC,
+    #[default]
     PRIVATE,
     PROTECTED
 }
 
-impl Default for AccessModifier {
-    fn default() -> Self {
-        AccessModifier::PRIVATE
-    }
-}
-
 #[derive(Debug, Default, Clone)]
 pub struct VarDeclaration {
-    pub type_def: Type,
+    pub doc_comment: Option<String>,
+    pub access_modifier: AccessModifier,
+    pub type_def: Option<Type>,
     pub identifier: String,
     pub definition: Option<Expression>,
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct ConstDeclaration {
+    pub doc_comment: Option<String>,
     pub access_modifier: AccessModifier,
-    pub type_name: String,
+    pub type_def: Type,
+    pub identifier: String,
+    pub definition: Expression,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Assignment {
     pub identifier: String,
+    pub indices: Vec<Expression>,
     pub definition: Expression,
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct FunctionDeclaration {
+    pub doc_comment: Option<String>,
     pub access_modifier: AccessModifier,
     pub is_async: bool,
     pub identifier: String,
@@ -149,31 +278,31 @@ pub struct FunctionDeclaration {
 #[derive(Debug, Default, Clone)]
 pub struct Parameter {
     pub identifier: String,
-    pub type_identifier: String,
-    pub type_class: ClassDeclaration,
+    pub type_def: Type,
+    /// Evaluated in the function scope on each call that leaves the parameter out
+    pub default_value: Option<Expression>,
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct Type {
     pub type_kind: TypeKind,
     pub type_identifier: String,
-    pub type_parameters: Vec<String>,
+    pub type_parameters: Vec<Type>,
     pub type_class: Option<ClassDeclaration>,
-    pub is_type_array: bool
+    pub is_type_array: bool,
+    pub element_type: Option<Box<Type>>,
+    pub return_type: Option<Box<Type>>,
 }
 
-#[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
 pub enum TypeKind {
     BUILTIN,
+    #[default]
     SIMPLE,
     GENERIC,
-    TYPEARRAY
-}
-
-impl Default for TypeKind {
-    fn default() -> Self {
-        TypeKind::SIMPLE
-    }
+    TYPEARRAY,
+    FUNCTION
 }
 
 #[derive(Debug, Default, Clone)]
@@ -192,6 +321,34 @@ pub struct Statement {
     pub if_statement: Option<IfStatement>,
     pub while_statement: Option<WhileStatement>,
     pub for_statement: Option<ForStatement>,
+    pub return_statement: Option<ReturnStatement>,
+    pub throw_statement: Option<ThrowStatement>,
+    pub try_statement: Option<TryStatement>,
+    pub assignment: Option<Assignment>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ReturnStatement {
+    pub expression: Option<Expression>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ThrowStatement {
+    pub expression: Expression,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct TryStatement {
+    pub block: Block,
+    pub catch_clauses: Vec<CatchClause>,
+    pub finally_block: Option<Block>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct CatchClause {
+    pub identifier: String,
+    pub type_def: Option<Type>,
+    pub block: Block,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -204,26 +361,25 @@ pub struct WhileStatement {
 pub struct ForStatement {
     pub for_statement_kind: ForStatementKind,
     pub variable: VarDeclaration,
-    pub binary_expression: BinaryExpression,
-    pub postfix_expression: AffixExpression,
+    pub condition: Expression,
+    pub step: Box<Statement>,
+    pub iterable: Expression,
     pub block: Block
 }
 
-#[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
 pub enum ForStatementKind {
+    #[default]
     CONDITIONAL,
     ITERATOR
 }
 
-impl Default for ForStatementKind {
-    fn default() -> Self {
-        ForStatementKind::CONDITIONAL
-    }
-}
-
 
-#[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
 pub enum StatementKind {
+    #[default]
     EXPRESSION,
     ASYNCEXPRESSION,
     CONSTDECLARATION,
@@ -231,13 +387,11 @@ pub enum StatementKind {
     BLOCK,
     IFSTMT,
     WHILESTMT,
-    FORSTMT
-}
-
-impl Default for StatementKind {
-    fn default() -> Self {
-        StatementKind::EXPRESSION
-    }
+    FORSTMT,
+    RETURNSTMT,
+    THROWSTMT,
+    TRYSTMT,
+    ASSIGNMENT
 }
 
 #[derive(Debug, Default, Clone)]
@@ -249,21 +403,18 @@ pub struct IfStatement {
     pub else_if_statement: Option<Box<IfStatement>>
 }
 
-#[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
 pub enum IfStatementKind {
     OPEN,
     CLOSED,
+    #[default]
     DOUBLE,
 }
 
-impl Default for IfStatementKind {
-    fn default() -> Self {
-        IfStatementKind::DOUBLE
-    }
-}
-
 #[derive(Debug, Default, Clone)]
 pub struct ClassDeclaration {
+    pub doc_comment: Option<String>,
     pub access_modifier: AccessModifier,
     pub is_static: bool,
     pub identifier: String,
@@ -307,8 +458,23 @@ pub struct ClassMethod {
     pub block: Block
 }
 
+#[derive(Debug, Default, Clone)]
+pub struct EnumDeclaration {
+    pub doc_comment: Option<String>,
+    pub access_modifier: AccessModifier,
+    pub identifier: String,
+    pub variants: Vec<EnumVariant>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct EnumVariant {
+    pub identifier: String,
+    pub payload_types: Vec<Type>,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct InterfaceDeclaration {
+    pub doc_comment: Option<String>,
     pub access_modifier: AccessModifier,
     pub identifier: String,
     pub methods: Vec<InterfaceMethodDeclaration>
@@ -327,40 +493,102 @@ pub struct InterfaceMethodDeclaration {
 pub struct ExecutionContext {
     pub scope_name: String,
     pub scope_type: ScopeType,
-    pub scoped_constants: Vec<ConstDeclaration>,
-    pub scoped_variables: Vec<VarDeclaration>,
+    pub scoped_variables: HashMap<String, Variable>,
     pub scoped_classes: Vec<ClassDeclaration>,
-    pub scoped_functions: Vec<FunctionDeclaration>,
     pub scoped_interfaces: Vec<InterfaceDeclaration>,
+    pub scoped_enums: Vec<Rc<EnumDeclaration>>,
+    /// User modules imported into this scope, by the name they are accessed with
+    pub scoped_modules: HashMap<String, Rc<Module>>,
+    /// Full paths of the native modules imported into this scope, by the name they are
+    /// accessed with
+    pub scoped_native_modules: HashMap<String, String>,
+    /// Declarations marked `public`, which other files can import
+    pub public_names: HashSet<String>,
+    pub parent: Option<Rc<RefCell<ExecutionContext>>>,
+}
+
+impl ExecutionContext {
+    pub fn new_child(
+        parent: &Rc<RefCell<ExecutionContext>>,
+        scope_name: &str,
+        scope_type: ScopeType,
+    ) -> Rc<RefCell<ExecutionContext>> {
+        Rc::new(RefCell::new(ExecutionContext {
+            scope_name: scope_name.to_string(),
+            scope_type,
+            parent: Some(parent.clone()),
+            ..Default::default()
+        }))
+    }
+
+    pub fn lookup(&self, identifier: &str) -> Option<Variable> {
+        match self.scoped_variables.get(identifier) {
+            Some(variable) => Some(variable.clone()),
+            None => self
+                .parent
+                .as_ref()
+                .and_then(|parent| parent.borrow().lookup(identifier)),
+        }
+    }
+
+    pub fn lookup_enum(&self, identifier: &str) -> Option<Rc<EnumDeclaration>> {
+        match self
+            .scoped_enums
+            .iter()
+            .find(|enum_declaration| enum_declaration.identifier == identifier)
+        {
+            Some(enum_declaration) => Some(enum_declaration.clone()),
+            None => self
+                .parent
+                .as_ref()
+                .and_then(|parent| parent.borrow().lookup_enum(identifier)),
+        }
+    }
+
+    pub fn lookup_module(&self, identifier: &str) -> Option<Rc<Module>> {
+        match self.scoped_modules.get(identifier) {
+            Some(module) => Some(module.clone()),
+            None => self
+                .parent
+                .as_ref()
+                .and_then(|parent| parent.borrow().lookup_module(identifier)),
+        }
+    }
+
+    pub fn lookup_native_module(&self, identifier: &str) -> Option<String> {
+        match self.scoped_native_modules.get(identifier) {
+            Some(full_path) => Some(full_path.clone()),
+            None => self
+                .parent
+                .as_ref()
+                .and_then(|parent| parent.borrow().lookup_native_module(identifier)),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
 pub enum ScopeType {
+    #[default]
     GLOBAL,
     CLASSDECLARATION,
     FUNCTIONDECLARATION,
+    BLOCK,
 }
 
-impl Default for ScopeType {
-    fn default() -> Self {
-        ScopeType::GLOBAL
-    }
-}
-
-#[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
 pub enum UnaryExpressionKind {
+    #[default]
     IDENTIFIER,
     LITERAL,
     FUNCTIONCALL,
+    LAMBDA,
+    MATCH,
+    AWAIT,
     EXPRESSION
 }
 
-impl Default for UnaryExpressionKind {
-    fn default() -> Self {
-        UnaryExpressionKind::IDENTIFIER
-    }
-}
-
 #[derive(Debug, Default, Clone)]
 pub struct Declaration {
     pub declaration_kind: DeclarationKind,
@@ -369,76 +597,55 @@ pub struct Declaration {
     pub class_declaration: Option<ClassDeclaration>,
     pub function_declaration: Option<FunctionDeclaration>,
     pub interface_declaration: Option<InterfaceDeclaration>,
+    pub enum_declaration: Option<EnumDeclaration>,
+}
+
+impl Declaration {
+    /// The declared name when the declaration is marked `public`.
+    pub fn public_identifier(&self) -> Option<&str> {
+        let (access_modifier, identifier) = match self.declaration_kind {
+            DeclarationKind::VAR => {
+                let declaration = self.var_declaration.as_ref().unwrap();
+                (&declaration.access_modifier, &declaration.identifier)
+            }
+            DeclarationKind::CONST => {
+                let declaration = self.const_declaration.as_ref().unwrap();
+                (&declaration.access_modifier, &declaration.identifier)
+            }
+            DeclarationKind::CLASS => {
+                let declaration = self.class_declaration.as_ref().unwrap();
+                (&declaration.access_modifier, &declaration.identifier)
+            }
+            DeclarationKind::FUNCTION => {
+                let declaration = self.function_declaration.as_ref().unwrap();
+                (&declaration.access_modifier, &declaration.identifier)
+            }
+            DeclarationKind::INTERFACE => {
+                let declaration = self.interface_declaration.as_ref().unwrap();
+                (&declaration.access_modifier, &declaration.identifier)
+            }
+            DeclarationKind::ENUM => {
+                let declaration = self.enum_declaration.as_ref().unwrap();
+                (&declaration.access_modifier, &declaration.identifier)
+            }
+        };
+
+        match access_modifier {
+            AccessModifier::PUBLIC => Some(identifier),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, Clone)]
 pub enum DeclarationKind {
+    #[default]
     VAR,
     CONST,
     CLASS,
     INTERFACE,
     FUNCTION,
+    ENUM,
 }
 
-impl Default for DeclarationKind {
-    fn default() -> Self {
-        DeclarationKind::VAR
-    }
-}
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-#[derive(Debug, Default, Clone)]
-struct test1<'a> {
-    test_type: testEnum<'a>
-}
-
-#[derive(Debug, Clone)]
-enum testEnum<'a> {
-    test2(test2<'a>),
-    test3(test3)
-}
-
-impl <'a>Default for testEnum<'a> {
-    fn default() -> Self {
-        testEnum::test2(test2::default())
-    }
-}
-
-#[derive(Debug, Default, Clone)]
-struct test2<'a> {
-    name: &'a str
-}
-
-#[derive(Debug, Default, Clone)]
-struct test3 {
-
-}
-
-fn wtt() {
-    let mut test = test1::default();
-    test.test_type = testEnum::test2(test2 {  name: "test"});
-
-    match test.test_type {
-        testEnum::test2(t2) => {
-            println!("{}", t2.name);
-        }
-        testEnum::test3(t3) => {}
-    }
-}
\ No newline at end of file
@@ -0,0 +1,922 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    runtime::{FunctionValue, Interrupt, SharedFuture, Value},
+    structure::{
+        Assignment, BinaryExpression, CatchClause, ExecutionContext, Expression, ExpressionKind,
+        ForStatement, ForStatementKind, IfStatement, Literal, LiteralKind, MatchExpression,
+        ScopeType, Statement, StatementKind, StringSegmentKind, UnaryExpression,
+        UnaryExpressionKind, VarDeclaration,
+    },
+};
+
+type Context = Rc<RefCell<ExecutionContext>>;
+
+/// The body of an async function, lowered so that it can stop at any `await` and resume
+/// later. Every `await` becomes a statement of its own, with what is evaluated before it
+/// kept in temporaries named `$0`, `$1`... that no program can declare. Statements without
+/// `await` are kept as they are.
+pub type TaskBlock = Rc<[TaskStatement]>;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug)]
+pub enum TaskStatement {
+    PLAIN(Box<Statement>),
+    /// `let $0 = await expression;`, the expression itself has no `await` left
+    AWAIT {
+        identifier: String,
+        expression: Expression,
+    },
+    BLOCK(TaskBlock),
+    IF {
+        condition: Expression,
+        block: TaskBlock,
+        else_block: Option<TaskBlock>,
+    },
+    WHILE(Rc<TaskWhile>),
+    FORIN(Rc<TaskForIn>),
+    TRY(Rc<TaskTry>),
+    MATCH(Rc<TaskMatch>),
+}
+
+/// `for (init; condition; step)` loops are lowered to a `while` in a block of their own.
+#[derive(Debug)]
+pub struct TaskWhile {
+    pub condition: Expression,
+    pub block: TaskBlock,
+}
+
+#[derive(Debug)]
+pub struct TaskForIn {
+    pub identifier: String,
+    pub iterable: Expression,
+    pub block: TaskBlock,
+}
+
+#[derive(Debug)]
+pub struct TaskTry {
+    pub block: TaskBlock,
+    pub catch_clauses: Vec<CatchClause>,
+    pub catch_blocks: Vec<TaskBlock>,
+    pub finally_block: Option<TaskBlock>,
+}
+
+/// A `match` with an `await` in its arms. Each arm assigns its value to a temporary, which
+/// replaces the `match` in the expression it was part of.
+#[derive(Debug)]
+pub struct TaskMatch {
+    pub match_expression: MatchExpression,
+    pub arm_blocks: Vec<TaskBlock>,
+}
+
+/// An async call. `frames` is its continuation: the blocks, loops and `try` statements it
+/// is in, innermost last.
+pub struct Task {
+    pub function: Rc<FunctionValue>,
+    pub frames: Vec<Frame>,
+    /// While suspended: the future awaited, and the temporary its value goes to
+    pub waiting: Option<(SharedFuture, String, Context)>,
+}
+
+impl Task {
+    /// A call of an async function, about to run its body in `function_context`.
+    pub fn new(function: Rc<FunctionValue>, function_context: Context) -> Self {
+        let statements = function.task_body.clone().unwrap();
+        Task {
+            function,
+            frames: vec![Frame::BLOCK {
+                statements,
+                position: 0,
+                context: function_context,
+            }],
+            waiting: None,
+        }
+    }
+
+    /// Enters a block, in a scope of its own nested in `context`.
+    pub fn push_block(&mut self, statements: TaskBlock, context: &Context) {
+        self.frames.push(Frame::BLOCK {
+            statements,
+            position: 0,
+            context: ExecutionContext::new_child(context, "block", ScopeType::BLOCK),
+        });
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
+pub enum Frame {
+    BLOCK {
+        statements: TaskBlock,
+        position: usize,
+        context: Context,
+    },
+    WHILE {
+        node: Rc<TaskWhile>,
+        context: Context,
+    },
+    FORIN {
+        node: Rc<TaskForIn>,
+        elements: Vec<Value>,
+        position: usize,
+        context: Context,
+    },
+    TRY {
+        node: Rc<TaskTry>,
+        stage: TryStage,
+        context: Context,
+    },
+}
+
+#[allow(clippy::upper_case_acronyms)]
+pub enum TryStage {
+    TRY,
+    CATCH,
+    /// Holds the `return` or error that `finally` interrupted, resumed once it is done
+    FINALLY(Option<Interrupt>),
+}
+
+/// Lowers the body of an async function, see `TaskBlock`.
+pub fn lower_body(statements: &[Statement]) -> TaskBlock {
+    Lowering::default().lower_block(statements)
+}
+
+#[derive(Default)]
+struct Lowering {
+    temporaries: usize,
+}
+
+impl Lowering {
+    fn temporary(&mut self) -> String {
+        self.temporaries += 1;
+        format!("${}", self.temporaries - 1)
+    }
+
+    fn lower_block(&mut self, statements: &[Statement]) -> TaskBlock {
+        let mut lowered = Vec::with_capacity(statements.len());
+        for statement in statements {
+            self.lower_statement(statement, &mut lowered);
+        }
+        lowered.into()
+    }
+
+    fn lower_statement(&mut self, statement: &Statement, out: &mut Vec<TaskStatement>) {
+        if !statement_awaits(statement) {
+            out.push(TaskStatement::PLAIN(Box::new(statement.clone())));
+            return;
+        }
+
+        let mut lowered = statement.clone();
+        match statement.statement_kind {
+            StatementKind::EXPRESSION => {
+                let expression = statement.expression.as_ref().unwrap();
+                lowered.expression = Some(self.lower_expression(expression, out));
+            }
+            StatementKind::ASYNCEXPRESSION => {
+                let expression = statement.async_expression.as_ref().unwrap();
+                let expression = self.lower_expression(expression, out);
+                let identifier = self.temporary();
+                out.push(TaskStatement::AWAIT {
+                    identifier,
+                    expression,
+                });
+                return;
+            }
+            StatementKind::CONSTDECLARATION => {
+                let const_declaration = lowered.const_declaration.as_mut().unwrap();
+                const_declaration.definition =
+                    self.lower_expression(&const_declaration.definition, out);
+            }
+            StatementKind::VARDECLARATION => {
+                let var_declaration = lowered.var_declaration.as_mut().unwrap();
+                if let Some(definition) = &var_declaration.definition {
+                    var_declaration.definition = Some(self.lower_expression(definition, out));
+                }
+            }
+            StatementKind::BLOCK => {
+                let block = statement.block.as_ref().unwrap();
+                out.push(TaskStatement::BLOCK(self.lower_block(&block.statements)));
+                return;
+            }
+            StatementKind::IFSTMT => {
+                self.lower_if(statement.if_statement.as_ref().unwrap(), out);
+                return;
+            }
+            StatementKind::WHILESTMT => {
+                let while_statement = statement.while_statement.as_ref().unwrap();
+                self.lower_loop(
+                    None,
+                    &while_statement.expression,
+                    &while_statement.block.statements,
+                    None,
+                    out,
+                );
+                return;
+            }
+            StatementKind::FORSTMT => {
+                self.lower_for(statement.for_statement.as_ref().unwrap(), out);
+                return;
+            }
+            StatementKind::RETURNSTMT => {
+                let return_statement = lowered.return_statement.as_mut().unwrap();
+                if let Some(expression) = &return_statement.expression {
+                    return_statement.expression = Some(self.lower_expression(expression, out));
+                }
+            }
+            StatementKind::THROWSTMT => {
+                let throw_statement = lowered.throw_statement.as_mut().unwrap();
+                throw_statement.expression =
+                    self.lower_expression(&throw_statement.expression, out);
+            }
+            StatementKind::TRYSTMT => {
+                let try_statement = statement.try_statement.as_ref().unwrap();
+                out.push(TaskStatement::TRY(Rc::new(TaskTry {
+                    block: self.lower_block(&try_statement.block.statements),
+                    catch_clauses: try_statement.catch_clauses.clone(),
+                    catch_blocks: try_statement
+                        .catch_clauses
+                        .iter()
+                        .map(|catch_clause| self.lower_block(&catch_clause.block.statements))
+                        .collect(),
+                    finally_block: try_statement
+                        .finally_block
+                        .as_ref()
+                        .map(|finally_block| self.lower_block(&finally_block.statements)),
+                })));
+                return;
+            }
+            StatementKind::ASSIGNMENT => {
+                // The value is evaluated before the indices of the target
+                let assignment = statement.assignment.as_ref().unwrap();
+                let parts: Vec<&Expression> = std::iter::once(&assignment.definition)
+                    .chain(&assignment.indices)
+                    .collect();
+                let mut parts = self.lower_sequence(&parts, out).into_iter();
+                let lowered_assignment = lowered.assignment.as_mut().unwrap();
+                lowered_assignment.definition = parts.next().unwrap();
+                lowered_assignment.indices = parts.collect();
+            }
+        }
+        out.push(TaskStatement::PLAIN(Box::new(lowered)));
+    }
+
+    /// Each `else if` is lowered into the `else` block, so that its condition is only
+    /// evaluated when the ones before it are false.
+    fn lower_if(&mut self, if_statement: &IfStatement, out: &mut Vec<TaskStatement>) {
+        let condition = self.lower_expression(&if_statement.expression, out);
+        let block = self.lower_block(&if_statement.block.statements);
+        let else_block = match (
+            &if_statement.else_if_statement,
+            &if_statement.else_statement,
+        ) {
+            (Some(else_if_statement), _) => {
+                let mut else_block = Vec::new();
+                self.lower_if(else_if_statement, &mut else_block);
+                Some(else_block.into())
+            }
+            (None, Some(else_statement)) => Some(self.lower_block(&else_statement.statements)),
+            (None, None) => None,
+        };
+        out.push(TaskStatement::IF {
+            condition,
+            block,
+            else_block,
+        });
+    }
+
+    fn lower_for(&mut self, for_statement: &ForStatement, out: &mut Vec<TaskStatement>) {
+        match for_statement.for_statement_kind {
+            ForStatementKind::ITERATOR => {
+                let iterable = self.lower_expression(&for_statement.iterable, out);
+                out.push(TaskStatement::FORIN(Rc::new(TaskForIn {
+                    identifier: for_statement.variable.identifier.clone(),
+                    iterable,
+                    block: self.lower_block(&for_statement.block.statements),
+                })));
+            }
+            ForStatementKind::CONDITIONAL => self.lower_loop(
+                Some(&for_statement.variable),
+                &for_statement.condition,
+                &for_statement.block.statements,
+                Some(&for_statement.step),
+                out,
+            ),
+        }
+    }
+
+    /// `while` and `for (init; condition; step)`. A condition that awaits is evaluated into
+    /// a temporary before the loop and again at the end of each iteration.
+    fn lower_loop(
+        &mut self,
+        variable: Option<&VarDeclaration>,
+        condition: &Expression,
+        statements: &[Statement],
+        step: Option<&Statement>,
+        out: &mut Vec<TaskStatement>,
+    ) {
+        let mut scope = Vec::new();
+        if let Some(variable) = variable {
+            let statement = Statement {
+                statement_kind: StatementKind::VARDECLARATION,
+                var_declaration: Some(variable.clone()),
+                ..Default::default()
+            };
+            self.lower_statement(&statement, &mut scope);
+        }
+
+        let mut block = vec![TaskStatement::BLOCK(self.lower_block(statements))];
+        if let Some(step) = step {
+            self.lower_statement(step, &mut block);
+        }
+
+        let condition = match expression_awaits(condition) {
+            true => {
+                let identifier = self.temporary();
+                let first = self.lower_expression(condition, &mut scope);
+                scope.push(TaskStatement::PLAIN(Box::new(let_statement(
+                    &identifier,
+                    first,
+                ))));
+                let next = self.lower_expression(condition, &mut block);
+                block.push(TaskStatement::PLAIN(Box::new(assign_statement(
+                    &identifier,
+                    next,
+                ))));
+                identifier_expression(&identifier)
+            }
+            false => condition.clone(),
+        };
+
+        scope.push(TaskStatement::WHILE(Rc::new(TaskWhile {
+            condition,
+            block: block.into(),
+        })));
+        out.push(TaskStatement::BLOCK(scope.into()));
+    }
+
+    /// Returns an equivalent expression without `await`, the statements that have to run
+    /// before it are pushed to `out`.
+    fn lower_expression(
+        &mut self,
+        expression: &Expression,
+        out: &mut Vec<TaskStatement>,
+    ) -> Expression {
+        if !expression_awaits(expression) {
+            return expression.clone();
+        }
+
+        let mut lowered = expression.clone();
+        match expression.expression_kind {
+            ExpressionKind::UNARY => {
+                return self
+                    .lower_unary_expression(expression.unary_expression.as_ref().unwrap(), out)
+            }
+            ExpressionKind::BINARY => {
+                let binary_expression = expression.binary_expression.as_ref().unwrap();
+                if let "&&" | "||" = binary_expression.operator.as_str() {
+                    if expression_awaits(&binary_expression.second_term) {
+                        return self.lower_short_circuit(binary_expression, out);
+                    }
+                }
+                let lowered_binary = lowered.binary_expression.as_mut().unwrap();
+                let parts = [
+                    &*binary_expression.first_term,
+                    &*binary_expression.second_term,
+                ];
+                let mut parts = self.lower_sequence(&parts, out).into_iter();
+                *lowered_binary.first_term = parts.next().unwrap();
+                *lowered_binary.second_term = parts.next().unwrap();
+            }
+            ExpressionKind::PREFIX => {
+                let prefix_expression = lowered.prefix_expression.as_mut().unwrap();
+                *prefix_expression.operand = self.lower_expression(&prefix_expression.operand, out);
+            }
+            ExpressionKind::POSTFIX => {
+                let postfix_expression = lowered.postfix_expression.as_mut().unwrap();
+                *postfix_expression.operand =
+                    self.lower_expression(&postfix_expression.operand, out);
+            }
+            ExpressionKind::MEMBERACCESS => {
+                // A bare name is looked up as it is, since it may be a module or an enum
+                let member_access = lowered.member_access.as_mut().unwrap();
+                let object = member_access
+                    .object
+                    .as_identifier()
+                    .is_none()
+                    .then(|| (*member_access.object).clone());
+                let arguments: Vec<Expression> = member_access
+                    .args_list
+                    .iter()
+                    .map(|argument| argument.expression.clone())
+                    .collect();
+                let parts: Vec<&Expression> = object.iter().chain(&arguments).collect();
+                let mut parts = self.lower_sequence(&parts, out).into_iter();
+                if object.is_some() {
+                    *member_access.object = parts.next().unwrap();
+                }
+                for (argument, expression) in member_access.args_list.iter_mut().zip(parts) {
+                    argument.expression = expression;
+                }
+            }
+            ExpressionKind::INDEX => {
+                let index_expression = lowered.index_expression.as_mut().unwrap();
+                let object = index_expression.object.as_deref().cloned();
+                let parts: Vec<&Expression> =
+                    object.iter().chain(&index_expression.indices).collect();
+                let mut parts = self.lower_sequence(&parts, out).into_iter();
+                if object.is_some() {
+                    index_expression.object = Some(Box::new(parts.next().unwrap()));
+                }
+                index_expression.indices = parts.collect();
+            }
+            ExpressionKind::CALL => {
+                let call_expression = expression.call_expression.as_ref().unwrap();
+                let arguments = call_expression
+                    .args_list
+                    .iter()
+                    .map(|argument| &argument.expression);
+                let parts: Vec<&Expression> = std::iter::once(&*call_expression.callee)
+                    .chain(arguments)
+                    .collect();
+                let mut parts = self.lower_sequence(&parts, out).into_iter();
+                let call_expression = lowered.call_expression.as_mut().unwrap();
+                *call_expression.callee = parts.next().unwrap();
+                for (argument, expression) in call_expression.args_list.iter_mut().zip(parts) {
+                    argument.expression = expression;
+                }
+            }
+        }
+        lowered
+    }
+
+    fn lower_unary_expression(
+        &mut self,
+        unary_expression: &UnaryExpression,
+        out: &mut Vec<TaskStatement>,
+    ) -> Expression {
+        let mut lowered = unary_expression.clone();
+        match unary_expression.kind {
+            UnaryExpressionKind::AWAIT => {
+                let expression =
+                    self.lower_expression(unary_expression.expression.as_ref().unwrap(), out);
+                let identifier = self.temporary();
+                out.push(TaskStatement::AWAIT {
+                    identifier: identifier.clone(),
+                    expression,
+                });
+                return identifier_expression(&identifier);
+            }
+            UnaryExpressionKind::EXPRESSION => {
+                return self.lower_expression(unary_expression.expression.as_ref().unwrap(), out)
+            }
+            UnaryExpressionKind::MATCH => {
+                return self.lower_match_expression(
+                    unary_expression.match_expression.as_ref().unwrap(),
+                    out,
+                )
+            }
+            UnaryExpressionKind::LITERAL => {
+                let literal = lowered.literal.as_mut().unwrap();
+                match literal.literal_kind {
+                    LiteralKind::STRING => {
+                        let parts: Vec<&Expression> = literal
+                            .segments
+                            .iter()
+                            .filter_map(|segment| segment.expression.as_ref())
+                            .collect();
+                        let parts = self.lower_sequence(&parts, out);
+                        let segments = literal.segments.iter_mut().filter(|segment| {
+                            matches!(segment.segment_kind, StringSegmentKind::INTERPOLATION)
+                        });
+                        for (segment, expression) in segments.zip(parts) {
+                            segment.expression = Some(expression);
+                        }
+                    }
+                    LiteralKind::ARRAY => {
+                        let parts: Vec<&Expression> = literal.elements.iter().collect();
+                        literal.elements = self.lower_sequence(&parts, out);
+                    }
+                    LiteralKind::MAP => {
+                        let parts: Vec<&Expression> = literal
+                            .entries
+                            .iter()
+                            .flat_map(|entry| [&entry.key, &entry.value])
+                            .collect();
+                        let mut parts = self.lower_sequence(&parts, out).into_iter();
+                        for entry in &mut literal.entries {
+                            entry.key = parts.next().unwrap();
+                            entry.value = parts.next().unwrap();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            UnaryExpressionKind::FUNCTIONCALL => {
+                let function_call = lowered.function_call.as_mut().unwrap();
+                let parts: Vec<Expression> = function_call
+                    .args_list
+                    .iter()
+                    .map(|argument| argument.expression.clone())
+                    .collect();
+                let parts: Vec<&Expression> = parts.iter().collect();
+                let parts = self.lower_sequence(&parts, out);
+                for (argument, expression) in function_call.args_list.iter_mut().zip(parts) {
+                    argument.expression = expression;
+                }
+            }
+            UnaryExpressionKind::IDENTIFIER | UnaryExpressionKind::LAMBDA => {}
+        }
+
+        Expression {
+            expression_kind: ExpressionKind::UNARY,
+            unary_expression: Some(lowered),
+            ..Default::default()
+        }
+    }
+
+    /// Lowers expressions evaluated one after the other. Those before the last one that
+    /// awaits are kept in temporaries, so that they are still evaluated first.
+    fn lower_sequence(
+        &mut self,
+        expressions: &[&Expression],
+        out: &mut Vec<TaskStatement>,
+    ) -> Vec<Expression> {
+        let last_await = match expressions
+            .iter()
+            .rposition(|expression| expression_awaits(expression))
+        {
+            Some(last_await) => last_await,
+            None => {
+                return expressions
+                    .iter()
+                    .map(|expression| (*expression).clone())
+                    .collect()
+            }
+        };
+
+        let mut lowered = Vec::with_capacity(expressions.len());
+        for (position, expression) in expressions.iter().enumerate() {
+            let expression = self.lower_expression(expression, out);
+            if position >= last_await || is_constant(&expression) {
+                lowered.push(expression);
+                continue;
+            }
+            let identifier = self.temporary();
+            out.push(TaskStatement::PLAIN(Box::new(let_statement(
+                &identifier,
+                expression,
+            ))));
+            lowered.push(identifier_expression(&identifier));
+        }
+        lowered
+    }
+
+    /// `a && await b` only awaits when `a` is true: the second term is evaluated in an `if`
+    /// and the operator is applied to both temporaries, which short-circuits the same way.
+    fn lower_short_circuit(
+        &mut self,
+        binary_expression: &BinaryExpression,
+        out: &mut Vec<TaskStatement>,
+    ) -> Expression {
+        let operator = binary_expression.operator.as_str();
+        let first = self.temporary();
+        let first_term = self.lower_expression(&binary_expression.first_term, out);
+        out.push(TaskStatement::PLAIN(Box::new(let_statement(
+            &first, first_term,
+        ))));
+        let second = self.temporary();
+        out.push(TaskStatement::PLAIN(Box::new(let_statement(
+            &second,
+            null_expression(),
+        ))));
+
+        let mut block = Vec::new();
+        let second_term = self.lower_expression(&binary_expression.second_term, &mut block);
+        block.push(TaskStatement::PLAIN(Box::new(assign_statement(
+            &second,
+            second_term,
+        ))));
+        out.push(TaskStatement::IF {
+            condition: binary_expression_of(
+                identifier_expression(&first),
+                "!=",
+                boolean_expression(operator == "||"),
+            ),
+            block: block.into(),
+            else_block: None,
+        });
+
+        binary_expression_of(
+            identifier_expression(&first),
+            operator,
+            identifier_expression(&second),
+        )
+    }
+
+    fn lower_match_expression(
+        &mut self,
+        match_expression: &MatchExpression,
+        out: &mut Vec<TaskStatement>,
+    ) -> Expression {
+        let mut lowered = match_expression.clone();
+        *lowered.expression = self.lower_expression(&match_expression.expression, out);
+
+        let arms_await = match_expression.arms.iter().any(|arm| {
+            arm.expression.as_ref().is_some_and(expression_awaits)
+                || arm
+                    .block
+                    .as_ref()
+                    .is_some_and(|block| block.statements.iter().any(statement_awaits))
+        });
+        if !arms_await {
+            return Expression {
+                expression_kind: ExpressionKind::UNARY,
+                unary_expression: Some(UnaryExpression {
+                    kind: UnaryExpressionKind::MATCH,
+                    match_expression: Some(lowered),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+        }
+
+        let target = self.temporary();
+        out.push(TaskStatement::PLAIN(Box::new(let_statement(
+            &target,
+            null_expression(),
+        ))));
+        let arm_blocks = match_expression
+            .arms
+            .iter()
+            .map(|arm| match (&arm.expression, &arm.block) {
+                (Some(expression), _) => {
+                    let mut block = Vec::new();
+                    let expression = self.lower_expression(expression, &mut block);
+                    block.push(TaskStatement::PLAIN(Box::new(assign_statement(
+                        &target, expression,
+                    ))));
+                    block.into()
+                }
+                (None, Some(block)) => self.lower_block(&block.statements),
+                (None, None) => Vec::new().into(),
+            })
+            .collect();
+        out.push(TaskStatement::MATCH(Rc::new(TaskMatch {
+            match_expression: lowered,
+            arm_blocks,
+        })));
+        identifier_expression(&target)
+    }
+}
+
+/// Whether running the statement may await. Lambdas are not async, an `await` in their
+/// body is rejected before the program runs.
+pub fn statement_awaits(statement: &Statement) -> bool {
+    let block_awaits = |statements: &[Statement]| statements.iter().any(statement_awaits);
+    match statement.statement_kind {
+        StatementKind::EXPRESSION => expression_awaits(statement.expression.as_ref().unwrap()),
+        StatementKind::ASYNCEXPRESSION => true,
+        StatementKind::CONSTDECLARATION => {
+            expression_awaits(&statement.const_declaration.as_ref().unwrap().definition)
+        }
+        StatementKind::VARDECLARATION => statement
+            .var_declaration
+            .as_ref()
+            .unwrap()
+            .definition
+            .as_ref()
+            .is_some_and(expression_awaits),
+        StatementKind::BLOCK => block_awaits(&statement.block.as_ref().unwrap().statements),
+        StatementKind::IFSTMT => if_awaits(statement.if_statement.as_ref().unwrap()),
+        StatementKind::WHILESTMT => {
+            let while_statement = statement.while_statement.as_ref().unwrap();
+            expression_awaits(&while_statement.expression)
+                || block_awaits(&while_statement.block.statements)
+        }
+        StatementKind::FORSTMT => {
+            let for_statement = statement.for_statement.as_ref().unwrap();
+            let header_awaits = match for_statement.for_statement_kind {
+                ForStatementKind::ITERATOR => expression_awaits(&for_statement.iterable),
+                ForStatementKind::CONDITIONAL => {
+                    for_statement
+                        .variable
+                        .definition
+                        .as_ref()
+                        .is_some_and(expression_awaits)
+                        || expression_awaits(&for_statement.condition)
+                        || statement_awaits(&for_statement.step)
+                }
+            };
+            header_awaits || block_awaits(&for_statement.block.statements)
+        }
+        StatementKind::RETURNSTMT => statement
+            .return_statement
+            .as_ref()
+            .unwrap()
+            .expression
+            .as_ref()
+            .is_some_and(expression_awaits),
+        StatementKind::THROWSTMT => {
+            expression_awaits(&statement.throw_statement.as_ref().unwrap().expression)
+        }
+        StatementKind::TRYSTMT => {
+            let try_statement = statement.try_statement.as_ref().unwrap();
+            block_awaits(&try_statement.block.statements)
+                || try_statement
+                    .catch_clauses
+                    .iter()
+                    .any(|catch_clause| block_awaits(&catch_clause.block.statements))
+                || try_statement
+                    .finally_block
+                    .as_ref()
+                    .is_some_and(|finally_block| block_awaits(&finally_block.statements))
+        }
+        StatementKind::ASSIGNMENT => {
+            let assignment: &Assignment = statement.assignment.as_ref().unwrap();
+            expression_awaits(&assignment.definition)
+                || assignment.indices.iter().any(expression_awaits)
+        }
+    }
+}
+
+fn if_awaits(if_statement: &IfStatement) -> bool {
+    expression_awaits(&if_statement.expression)
+        || if_statement.block.statements.iter().any(statement_awaits)
+        || if_statement
+            .else_if_statement
+            .as_deref()
+            .is_some_and(if_awaits)
+        || if_statement
+            .else_statement
+            .as_ref()
+            .is_some_and(|else_statement| else_statement.statements.iter().any(statement_awaits))
+}
+
+pub fn expression_awaits(expression: &Expression) -> bool {
+    let arguments_await = |arguments: &[crate::structure::Argument]| {
+        arguments
+            .iter()
+            .any(|argument| expression_awaits(&argument.expression))
+    };
+    match expression.expression_kind {
+        ExpressionKind::UNARY => {
+            let unary_expression = expression.unary_expression.as_ref().unwrap();
+            match unary_expression.kind {
+                UnaryExpressionKind::AWAIT => true,
+                UnaryExpressionKind::IDENTIFIER | UnaryExpressionKind::LAMBDA => false,
+                UnaryExpressionKind::EXPRESSION => {
+                    expression_awaits(unary_expression.expression.as_ref().unwrap())
+                }
+                UnaryExpressionKind::FUNCTIONCALL => {
+                    arguments_await(&unary_expression.function_call.as_ref().unwrap().args_list)
+                }
+                UnaryExpressionKind::LITERAL => {
+                    literal_awaits(unary_expression.literal.as_ref().unwrap())
+                }
+                UnaryExpressionKind::MATCH => {
+                    let match_expression = unary_expression.match_expression.as_ref().unwrap();
+                    expression_awaits(&match_expression.expression)
+                        || match_expression.arms.iter().any(|arm| {
+                            arm.guard.as_ref().is_some_and(expression_awaits)
+                                || arm.expression.as_ref().is_some_and(expression_awaits)
+                                || arm.block.as_ref().is_some_and(|block| {
+                                    block.statements.iter().any(statement_awaits)
+                                })
+                        })
+                }
+            }
+        }
+        ExpressionKind::BINARY => {
+            let binary_expression = expression.binary_expression.as_ref().unwrap();
+            expression_awaits(&binary_expression.first_term)
+                || expression_awaits(&binary_expression.second_term)
+        }
+        ExpressionKind::PREFIX => {
+            expression_awaits(&expression.prefix_expression.as_ref().unwrap().operand)
+        }
+        ExpressionKind::POSTFIX => {
+            expression_awaits(&expression.postfix_expression.as_ref().unwrap().operand)
+        }
+        ExpressionKind::MEMBERACCESS => {
+            let member_access = expression.member_access.as_ref().unwrap();
+            expression_awaits(&member_access.object) || arguments_await(&member_access.args_list)
+        }
+        ExpressionKind::INDEX => {
+            let index_expression = expression.index_expression.as_ref().unwrap();
+            index_expression
+                .object
+                .as_deref()
+                .is_some_and(expression_awaits)
+                || index_expression.indices.iter().any(expression_awaits)
+        }
+        ExpressionKind::CALL => {
+            let call_expression = expression.call_expression.as_ref().unwrap();
+            expression_awaits(&call_expression.callee)
+                || arguments_await(&call_expression.args_list)
+        }
+    }
+}
+
+fn literal_awaits(literal: &Literal) -> bool {
+    literal
+        .segments
+        .iter()
+        .any(|segment| segment.expression.as_ref().is_some_and(expression_awaits))
+        || literal.elements.iter().any(expression_awaits)
+        || literal
+            .entries
+            .iter()
+            .any(|entry| expression_awaits(&entry.key) || expression_awaits(&entry.value))
+}
+
+/// Literals that evaluate to the same value whenever they are evaluated.
+fn is_constant(expression: &Expression) -> bool {
+    match (&expression.expression_kind, &expression.unary_expression) {
+        (ExpressionKind::UNARY, Some(unary_expression)) => match &unary_expression.literal {
+            Some(literal) => match literal.literal_kind {
+                LiteralKind::STRING => literal.segments.iter().all(|segment| {
+                    !matches!(segment.segment_kind, StringSegmentKind::INTERPOLATION)
+                }),
+                LiteralKind::ARRAY | LiteralKind::MAP => false,
+                _ => true,
+            },
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+fn identifier_expression(identifier: &str) -> Expression {
+    Expression {
+        expression_kind: ExpressionKind::UNARY,
+        unary_expression: Some(UnaryExpression {
+            kind: UnaryExpressionKind::IDENTIFIER,
+            string_value: Some(identifier.to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn literal_expression(literal_kind: LiteralKind, value: &str) -> Expression {
+    Expression {
+        expression_kind: ExpressionKind::UNARY,
+        unary_expression: Some(UnaryExpression {
+            kind: UnaryExpressionKind::LITERAL,
+            literal: Some(Literal {
+                literal_kind,
+                value: value.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn null_expression() -> Expression {
+    literal_expression(LiteralKind::NULL, "null")
+}
+
+fn boolean_expression(value: bool) -> Expression {
+    literal_expression(LiteralKind::BOOLEAN, &value.to_string())
+}
+
+fn binary_expression_of(
+    first_term: Expression,
+    operator: &str,
+    second_term: Expression,
+) -> Expression {
+    Expression {
+        expression_kind: ExpressionKind::BINARY,
+        binary_expression: Some(BinaryExpression {
+            first_term: Box::new(first_term),
+            operator: operator.to_string(),
+            second_term: Box::new(second_term),
+        }),
+        ..Default::default()
+    }
+}
+
+fn let_statement(identifier: &str, definition: Expression) -> Statement {
+    Statement {
+        statement_kind: StatementKind::VARDECLARATION,
+        var_declaration: Some(VarDeclaration {
+            identifier: identifier.to_string(),
+            definition: Some(definition),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn assign_statement(identifier: &str, definition: Expression) -> Statement {
+    Statement {
+        statement_kind: StatementKind::ASSIGNMENT,
+        assignment: Some(Assignment {
+            identifier: identifier.to_string(),
+            indices: Vec::new(),
+            definition,
+        }),
+        ..Default::default()
+    }
+}
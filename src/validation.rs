@@ -0,0 +1,121 @@
+use pest::iterators::Pair;
+
+use crate::{
+    errors::{ProgramError, RuntimeError},
+    handlers::handle_escape_sequence,
+    numeric::{parse_float_literal, parse_integer_literal},
+    Rule,
+};
+
+/// What a pair is part of when `await` cannot be used there, and what to do instead
+type Forbidden = (&'static str, &'static str);
+
+const OUTSIDE_ASYNC: &str = "Only async functions and the top level of a file can `await`";
+const IN_GUARD: Forbidden = ("a `match` guard", "Await the value before the `match`");
+
+/// Checks a whole file before any of it runs, for what the grammar accepts but the
+/// interpreter cannot run.
+pub fn validate_program(program_pair: &Pair<'_, Rule>) -> Result<(), Box<ProgramError>> {
+    validate(program_pair.clone(), None)
+}
+
+/// Literals are checked here so that a bad one fails even if it never runs.
+///
+/// Only the top level blocks on a future, and only async functions are lowered into tasks
+/// that can stop at an `await`, their parameter defaults and `match` guards excluded.
+fn validate(
+    pair: Pair<'_, Rule>,
+    await_forbidden_in: Option<Forbidden>,
+) -> Result<(), Box<ProgramError>> {
+    let await_forbidden_in = match pair.as_rule() {
+        Rule::r#await => {
+            return match await_forbidden_in {
+                Some((construct, suggestion)) => Err(error_at(
+                    &pair,
+                    RuntimeError::syntax_error(&format!("`await` cannot be used in {construct}"))
+                        .with_suggestion(suggestion),
+                )),
+                None => Ok(()),
+            }
+        }
+        Rule::escape_sequence => {
+            return match handle_escape_sequence(pair.clone()) {
+                Some(_) => Ok(()),
+                None => Err(error_at(
+                    &pair,
+                    RuntimeError::syntax_error(&format!(
+                        "Invalid escape `{}`: not a Unicode scalar value",
+                        pair.as_str()
+                    ))
+                    .with_suggestion(
+                        "Use a code point up to 10FFFF outside the surrogate range D800-DFFF",
+                    ),
+                )),
+            }
+        }
+        Rule::integer_literal => {
+            return parse_integer_literal(pair.as_str())
+                .map(|_| ())
+                .map_err(|error| error_at(&pair, error))
+        }
+        Rule::float_literal => {
+            return parse_float_literal(pair.as_str())
+                .map(|_| ())
+                .map_err(|error| error_at(&pair, error))
+        }
+        Rule::function_declaration | Rule::method_declaration => {
+            match pair
+                .clone()
+                .into_inner()
+                .any(|inner| inner.as_rule() == Rule::r#async)
+            {
+                true => None,
+                false => Some(("a function that is not `async`", OUTSIDE_ASYNC)),
+            }
+        }
+        Rule::constructor => Some(("a constructor", OUTSIDE_ASYNC)),
+        Rule::property_declaration => Some(("a property declaration", OUTSIDE_ASYNC)),
+        Rule::lambda_expression => Some(("a lambda", OUTSIDE_ASYNC)),
+        Rule::parameter => Some((
+            "a parameter default",
+            "Await the value in the function body",
+        )),
+        Rule::match_arm => return validate_match_arm(pair, await_forbidden_in),
+        _ => await_forbidden_in,
+    };
+
+    for inner_pair in pair.into_inner() {
+        validate(inner_pair, await_forbidden_in)?;
+    }
+    Ok(())
+}
+
+/// A guard is the expression between the pattern and the arm, when there are three parts.
+fn validate_match_arm(
+    pair: Pair<'_, Rule>,
+    await_forbidden_in: Option<Forbidden>,
+) -> Result<(), Box<ProgramError>> {
+    let inner_pairs: Vec<_> = pair.into_inner().collect();
+    let guard_position = (inner_pairs.len() == 3).then_some(1);
+
+    for (position, inner_pair) in inner_pairs.into_iter().enumerate() {
+        match Some(position) == guard_position {
+            true => validate(inner_pair, Some(IN_GUARD))?,
+            false => validate(inner_pair, await_forbidden_in)?,
+        }
+    }
+    Ok(())
+}
+
+/// Points at `pair` in its line, the way errors of a top level item do.
+fn error_at(pair: &Pair<'_, Rule>, error: RuntimeError) -> Box<ProgramError> {
+    let position = pair.as_span().start_pos();
+    Box::new(ProgramError {
+        error: RuntimeError {
+            identifier: Some(pair.as_str().to_string()),
+            ..error
+        },
+        span_str: position.line_of().trim().to_string(),
+        line: position.line_col().0,
+    })
+}
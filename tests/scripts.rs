@@ -0,0 +1,133 @@
+//! Runs every script in this directory and its subdirectories with the interpreter and
+//! compares what it prints and how it exits with the files checked in next to it:
+//! `name.stdout`, `name.stderr` and `name.status`, a missing one standing for no output
+//! and exit code 0.
+//!
+//! A script can also have `name.stdin`, given as its input, and `name.args`, the words
+//! to call the interpreter with instead of the script path alone. `{script}` and
+//! `{interpreter}` in it are replaced with their paths.
+//!
+//! Run with `BLESS=1` to write the expected files from the current output instead.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+const INTERPRETER: &str = env!("CARGO_BIN_EXE_interpreter2");
+
+/// Modules imported by the scripts, not scripts of their own
+const MODULE_DIRECTORIES: &[&str] = &["utils"];
+
+#[test]
+fn scripts_match_expected_output() {
+    let bless = std::env::var_os("BLESS").is_some();
+    let mut scripts = Vec::new();
+    collect_scripts(Path::new("tests"), &mut scripts);
+    scripts.sort();
+    assert!(!scripts.is_empty(), "no scripts found in tests/");
+
+    let failures: Vec<String> = scripts
+        .iter()
+        .filter_map(|script| check_script(script, bless).err())
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} scripts did not match, run with BLESS=1 to update the expected files\n\n{}",
+        failures.len(),
+        scripts.len(),
+        failures.join("\n\n")
+    );
+}
+
+fn collect_scripts(directory: &Path, scripts: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            if !MODULE_DIRECTORIES.contains(&name) {
+                collect_scripts(&path, scripts);
+            }
+        } else if path.extension().is_some_and(|extension| extension == "ws") {
+            scripts.push(path);
+        }
+    }
+}
+
+fn check_script(script: &Path, bless: bool) -> Result<(), String> {
+    let output = run_script(script);
+    let actual = [
+        ("stdout", String::from_utf8_lossy(&output.stdout).into_owned()),
+        ("stderr", String::from_utf8_lossy(&output.stderr).into_owned()),
+        ("status", status_text(output.status.code())),
+    ];
+
+    let mut mismatches = Vec::new();
+    for (extension, actual) in actual {
+        let expected_path = script.with_extension(extension);
+        let missing = match extension {
+            "status" => "0\n",
+            _ => "",
+        };
+        if bless {
+            match actual == missing {
+                true => {
+                    let _ = fs::remove_file(&expected_path);
+                }
+                false => fs::write(&expected_path, &actual).unwrap(),
+            }
+            continue;
+        }
+
+        let expected =
+            fs::read_to_string(&expected_path).unwrap_or_else(|_| missing.to_string());
+        if expected != actual {
+            mismatches.push(format!(
+                "--- {} expected\n{expected}--- {extension} actual\n{actual}",
+                expected_path.display()
+            ));
+        }
+    }
+
+    match mismatches.is_empty() {
+        true => Ok(()),
+        false => Err(format!("{}:\n{}", script.display(), mismatches.join(""))),
+    }
+}
+
+fn run_script(script: &Path) -> std::process::Output {
+    let script_path = script.to_str().unwrap();
+    let arguments: Vec<String> = match fs::read_to_string(script.with_extension("args")) {
+        Ok(arguments) => arguments
+            .split_whitespace()
+            .map(|word| {
+                word.replace("{script}", script_path)
+                    .replace("{interpreter}", INTERPRETER)
+            })
+            .collect(),
+        Err(_) => vec![script_path.to_string()],
+    };
+    let input = fs::read(script.with_extension("stdin")).unwrap_or_default();
+
+    let mut child = Command::new(INTERPRETER)
+        .args(&arguments)
+        .env("NO_COLOR", "1")
+        .env_remove("CLICOLOR_FORCE")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn status_text(code: Option<i32>) -> String {
+    match code {
+        Some(code) => format!("{code}\n"),
+        None => String::from("killed by a signal\n"),
+    }
+}